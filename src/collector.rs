@@ -4,8 +4,8 @@ use chrono::Datelike;
 use serde::Deserialize;
 
 use crate::{
-    ClanStorage, ClanTag, CwlWarStats, MemberWarStats, PlayerGamesStats, PlayerTag, Season,
-    Storage, WarAttack, WarStats, WarTag,
+    ClanStorage, ClanTag, Clock, MemberWarStats, PlayerTag, RaidAttack, RaidMember,
+    RaidWeekendStats, Season, Storage, WarAttack, WarStats, WarTag,
 };
 
 mod api;
@@ -14,7 +14,7 @@ mod warclient;
 pub use warclient::*;
 
 mod time;
-pub use time::Time;
+pub use time::{Time, TimeError};
 
 mod cwl;
 pub use cwl::*;
@@ -167,14 +167,95 @@ pub struct CapitalRaidWeekend {
     offensiveReward: usize,
     defensiveReward: usize,
     pub members: Option<Vec<CapitalRaidWeekendMember>>,
-    pub attackLog: Vec<serde_json::Value>,
+    pub attackLog: Vec<RaidAttackLogEntry>,
     defenseLog: Vec<serde_json::Value>,
 }
 
+/// The Attacks on the Capital of a single other Clan during a Raid Weekend
+#[derive(Debug, Deserialize)]
+pub struct RaidAttackLogEntry {
+    pub defender: RaidDefender,
+    #[serde(default)]
+    pub districts: Vec<RaidDistrict>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RaidDefender {
+    pub tag: ClanTag,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RaidDistrict {
+    pub name: String,
+    #[serde(rename = "districtHallLevel")]
+    pub district_hall_level: usize,
+    #[serde(default)]
+    pub attacks: Vec<RaidDistrictAttack>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RaidDistrictAttack {
+    pub attacker: RaidAttacker,
+    #[serde(rename = "destructionPercent")]
+    pub destruction_percent: usize,
+    pub stars: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RaidAttacker {
+    pub tag: PlayerTag,
+}
+
+impl CapitalRaidWeekend {
+    /// Converts the Weekend into the stored Stats, combining the Member List with the
+    /// individual Attacks from the Attack Log, [`None`] if the Weekend has no Member List
+    pub fn into_stats(self) -> Option<RaidWeekendStats> {
+        let mut attacks: HashMap<PlayerTag, Vec<RaidAttack>> = HashMap::new();
+        for entry in self.attackLog {
+            for district in entry.districts {
+                for attack in district.attacks {
+                    attacks
+                        .entry(attack.attacker.tag)
+                        .or_default()
+                        .push(RaidAttack {
+                            defender: entry.defender.tag.clone(),
+                            district: district.name.clone(),
+                            district_hall_level: district.district_hall_level,
+                            destruction: attack.destruction_percent,
+                            stars: attack.stars,
+                        });
+                }
+            }
+        }
+
+        let members = self
+            .members?
+            .into_iter()
+            .map(|member| {
+                let member_attacks = attacks.remove(&member.tag).unwrap_or_default();
+                (
+                    member.tag,
+                    RaidMember {
+                        looted: member.capitalResourcesLooted,
+                        attacks_used: member.attacks,
+                        attacks_available: member.attackLimit + member.bonusAttackLimit,
+                        attacks: member_attacks,
+                    },
+                )
+            })
+            .collect();
+
+        Some(RaidWeekendStats {
+            start_time: self.startTime,
+            members,
+        })
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CapitalRaidWeekendMember {
     attackLimit: usize,
-    attacks: usize,
+    pub attacks: usize,
     bonusAttackLimit: usize,
     pub capitalResourcesLooted: usize,
     name: String,
@@ -208,12 +289,8 @@ impl Client {
         let resp = self
             .client
             .get(format!(
-                "https://api.clashofclans.com/v1/clans/%23{}/currentwar/leaguegroup",
-                clan_tag
-                    .0
-                    .as_str()
-                    .strip_prefix("#")
-                    .unwrap_or(clan_tag.0.as_str())
+                "https://api.clashofclans.com/v1/clans/{}/currentwar/leaguegroup",
+                clan_tag.url_encoded()
             ))
             .bearer_auth(&self.api_key)
             .send()
@@ -241,12 +318,8 @@ impl Client {
         let resp = self
             .client
             .get(format!(
-                "https://api.clashofclans.com/v1/clanwarleagues/wars/%23{}",
-                war_tag
-                    .0
-                    .as_str()
-                    .strip_prefix("#")
-                    .unwrap_or(war_tag.0.as_str())
+                "https://api.clashofclans.com/v1/clanwarleagues/wars/{}",
+                war_tag.url_encoded()
             ))
             .bearer_auth(&self.api_key)
             .send()
@@ -270,8 +343,8 @@ impl Client {
         let resp = self
             .client
             .get(format!(
-                "https://api.clashofclans.com/v1/clans/%23{}",
-                clan.0.as_str().strip_prefix("#").unwrap_or(clan.0.as_str())
+                "https://api.clashofclans.com/v1/clans/{}",
+                clan.url_encoded()
             ))
             .bearer_auth(&self.api_key)
             .send()
@@ -291,15 +364,16 @@ impl Client {
         resp.json().await.map_err(|e| LoadError::Deserialize(e))
     }
 
-    pub async fn captial_raid_seasons(
+    /// Loads the most recent Raid Weekends, including the Attack Log of each of them
+    pub async fn get_capital_raid_log(
         &self,
         clan: &ClanTag,
     ) -> Result<CapitalRaidWeekendLogs, LoadError> {
         let resp = self
             .client
             .get(format!(
-                "https://api.clashofclans.com/v1/clans/%23{}/capitalraidseasons?limit=5",
-                clan.0.as_str().strip_prefix("#").unwrap_or(clan.0.as_str())
+                "https://api.clashofclans.com/v1/clans/{}/capitalraidseasons?limit=5",
+                clan.url_encoded()
             ))
             .bearer_auth(&self.api_key)
             .send()
@@ -319,16 +393,42 @@ impl Client {
         resp.json().await.map_err(|e| LoadError::Deserialize(e))
     }
 
+    /// Loads all the Members of the Clan, following the Pagination of the API
+    pub async fn clan_members(&self, clan: &ClanTag) -> Result<Vec<ClanMember>, LoadError> {
+        let tag = clan.url_encoded();
+
+        api::collect_pages(|cursor| {
+            let mut url = format!(
+                "https://api.clashofclans.com/v1/clans/{}/members?limit=50",
+                tag
+            );
+            if let Some(cursor) = cursor {
+                url.push_str("&after=");
+                url.push_str(&cursor);
+            }
+
+            let request = self.client.get(url).bearer_auth(&self.api_key).send();
+            async move {
+                let resp = request.await.map_err(LoadError::ReqwestError)?;
+
+                if !resp.status().is_success() {
+                    return Err(LoadError::NotOkResponse(resp.status()));
+                }
+
+                resp.json::<api::Page<ClanMember>>()
+                    .await
+                    .map_err(LoadError::Deserialize)
+            }
+        })
+        .await
+    }
+
     pub async fn player_info(&self, player: &PlayerTag) -> Result<PlayerInfo, LoadError> {
         let resp = self
             .client
             .get(format!(
-                "https://api.clashofclans.com/v1/players/%23{}",
-                player
-                    .0
-                    .as_str()
-                    .strip_prefix("#")
-                    .unwrap_or(player.0.as_str())
+                "https://api.clashofclans.com/v1/players/{}",
+                player.url_encoded()
             ))
             .bearer_auth(&self.api_key)
             .send()
@@ -355,31 +455,80 @@ pub async fn update_names(
     clan: &ClanTag,
     clan_season_stats: &mut ClanStorage,
 ) -> Result<(), ()> {
-    let info = match client.clan_info(clan).await {
-        Ok(i) => i,
+    let members = match client.clan_members(clan).await {
+        Ok(m) => m,
+        Err(e) => {
+            tracing::error!("Failed to load Clan Members {:?}", e);
+            return Err(());
+        }
+    };
+
+    clan_season_stats
+        .ingest_names(members.into_iter().map(|member| (member.tag, member.name)))
+        .map_err(|e| {
+            tracing::error!("Updating Player Names: {:?}", e);
+        })
+}
+
+#[tracing::instrument(skip(client, storage, clock))]
+pub async fn update_raids(
+    client: &Client,
+    clan: &ClanTag,
+    storage: &mut Storage,
+    clock: &dyn Clock,
+) -> Result<(), ()> {
+    let raids = match client.get_capital_raid_log(clan).await {
+        Ok(r) => r,
         Err(e) => {
-            tracing::error!("Failed to load Clan Information {:?}", e);
+            tracing::error!("Error loading Capital Raid Seasons: {:?}", e);
             return Err(());
         }
     };
 
-    clan_season_stats.player_names.clear();
-    for member in info.memberList {
-        clan_season_stats
-            .player_names
-            .insert(member.tag, member.name);
+    for raid in raids.items {
+        tracing::debug!("Start-Time: {:?}", raid.startTime);
+
+        let stats = match raid.into_stats() {
+            Some(s) => s,
+            None => {
+                tracing::trace!("Skipping weekend, because there is no member list");
+                continue;
+            }
+        };
+
+        let clan_season_stats = match storage.get_mut(clan, &stats.start_time.into()) {
+            Some(s) => s,
+            None => {
+                tracing::error!("Getting Stats entry for Clan {:?}", clan);
+                return Err(());
+            }
+        };
+
+        if clan_season_stats.raid_weekend.get(&stats.start_time) == Some(&stats) {
+            tracing::trace!("Raid Weekend is already stored");
+            continue;
+        }
+
+        match clan_season_stats.ingest_raid(stats) {
+            Ok(_) => clan_season_stats.touch(clock.now().into()),
+            Err(e) => tracing::error!("Updating Raid Weekend: {:?}", e),
+        }
     }
 
     Ok(())
 }
 
-#[tracing::instrument(skip(client, storage))]
-pub async fn update_cwl(client: &Client, clan: &ClanTag, storage: &mut Storage) -> Result<(), ()> {
+#[tracing::instrument(skip(client, storage, clock))]
+pub async fn update_cwl(
+    client: &Client,
+    clan: &ClanTag,
+    storage: &mut Storage,
+    clock: &dyn Clock,
+) -> Result<(), ()> {
     let w = match client.clan_war_league_group(clan).await {
         Ok(w) => w,
         Err(LoadError::NotOkResponse(resp)) if resp == reqwest::StatusCode::NOT_FOUND => {
-            let now = chrono::Utc::now();
-            if now.day() > 10 {
+            if clock.now().day() > 10 {
                 tracing::debug!("Ignoring CWL Not found");
                 return Ok(());
             } else {
@@ -408,55 +557,69 @@ pub async fn update_cwl(client: &Client, clan: &ClanTag, storage: &mut Storage)
                     continue;
                 }
 
-                let clan = if &w.clan.tag == clan {
-                    w.clan
+                let (clan, opponent) = if &w.clan.tag == clan {
+                    (w.clan, w.opponent)
                 } else {
-                    w.opponent
+                    (w.opponent, w.clan)
                 };
 
-                if clan_season_stats.cwl.wars.len() <= round_index {
-                    clan_season_stats.cwl.wars.extend(
-                        (0..(clan_season_stats.cwl.wars.len() - round_index) + 1).map(|_| {
-                            CwlWarStats {
-                                members: HashMap::new(),
-                            }
-                        }),
-                    );
-                }
-                let cwl_stats = clan_season_stats.cwl.wars.get_mut(round_index).expect("");
-
-                for member in clan.members.iter() {
-                    let member_stats =
-                        cwl_stats
-                            .members
-                            .entry(member.tag.clone())
-                            .or_insert_with(|| MemberWarStats {
-                                attacks: Vec::new(),
-                            });
-
-                    if let Some(attacks) = &member.attacks {
-                        member_stats.attacks = attacks
+                let opponent_townhalls: HashMap<&PlayerTag, u8> = opponent
+                    .members
+                    .iter()
+                    .filter_map(|member| {
+                        u8::try_from(member.townhallLevel)
+                            .ok()
+                            .map(|th| (&member.tag, th))
+                    })
+                    .collect();
+
+                let members = clan
+                    .members
+                    .iter()
+                    .map(|member| {
+                        let attacks = member
+                            .attacks
                             .iter()
+                            .flatten()
                             .map(|raw_attack| WarAttack {
                                 destruction: raw_attack.destructionPercentage,
                                 stars: raw_attack.stars,
                                 duration: raw_attack.duration,
+                                target: Some(raw_attack.defenderTag.clone()),
+                                order: raw_attack.order,
+                                attacker_townhall: u8::try_from(member.townhallLevel).ok(),
+                                defender_townhall: opponent_townhalls
+                                    .get(&raw_attack.defenderTag)
+                                    .copied(),
                             })
                             .collect();
-                    }
+
+                        (member.tag.clone(), MemberWarStats { attacks })
+                    })
+                    .collect();
+
+                if let Err(e) = clan_season_stats.ingest_cwl_round(
+                    wtag.clone(),
+                    u8::try_from(round_index).unwrap_or(u8::MAX),
+                    members,
+                ) {
+                    tracing::error!("Updating CWL Round: {:?}", e);
+                    return Err(());
                 }
             }
         }
     }
+    clan_season_stats.touch(clock.now().into());
 
     Ok(())
 }
 
-#[tracing::instrument(skip(client, storage))]
+#[tracing::instrument(skip(client, storage, clock))]
 pub async fn update_war(
     client: &Client,
     clan_tag: &ClanTag,
     storage: &mut Storage,
+    clock: &dyn Clock,
 ) -> Result<(), ()> {
     let war = match client.war().current(&clan_tag).await {
         Ok(w) => w,
@@ -466,10 +629,29 @@ pub async fn update_war(
         }
     };
 
-    if !matches!(war.state, CurrentWarState::InWar) {
-        tracing::info!("WAR: Not in War currently {:?}", war.state);
-        return Ok(());
-    }
+    let ended = match war.state {
+        CurrentWarState::InWar => false,
+        CurrentWarState::Ended => true,
+        state => {
+            tracing::info!("WAR: Not in War currently {:?}", state);
+            return Ok(());
+        }
+    };
+
+    let result = ended.then(|| war.result());
+    let opponent = war.opponent.tag.clone();
+
+    let opponent_townhalls: HashMap<PlayerTag, u8> = war
+        .opponent
+        .members
+        .into_iter()
+        .flatten()
+        .filter_map(|member| {
+            u8::try_from(member.townhallLevel)
+                .ok()
+                .map(|th| (member.tag, th))
+        })
+        .collect();
 
     let clan = war.clan;
     let members = match clan.members {
@@ -496,9 +678,13 @@ pub async fn update_war(
 
     let war_stats = WarStats {
         start_time: start_time.clone(),
+        attacks_per_member: war.attacks_per_member.unwrap_or(2),
+        opponent,
+        result,
         members: members
             .into_iter()
             .filter_map(|member| {
+                let attacker_townhall = u8::try_from(member.townhallLevel).ok();
                 let war_attacks = member
                     .attacks
                     .into_iter()
@@ -506,6 +692,10 @@ pub async fn update_war(
                         destruction: rattack.destructionPercentage,
                         stars: rattack.stars,
                         duration: rattack.duration,
+                        defender_townhall: opponent_townhalls.get(&rattack.defenderTag).copied(),
+                        target: Some(rattack.defenderTag),
+                        order: rattack.order,
+                        attacker_townhall,
                     })
                     .collect();
 
@@ -518,16 +708,20 @@ pub async fn update_war(
             })
             .collect(),
     };
-    clan_season_stats.wars.insert(start_time, war_stats);
+    clan_season_stats.ingest_war(war_stats).map_err(|e| {
+        tracing::error!("Updating War: {:?}", e);
+    })?;
+    clan_season_stats.touch(clock.now().into());
 
     Ok(())
 }
 
-#[tracing::instrument(skip(client, storage))]
+#[tracing::instrument(skip(client, storage, clock))]
 pub async fn update_clan_games(
     client: &Client,
     clan_tag: &ClanTag,
     storage: &mut Storage,
+    clock: &dyn Clock,
 ) -> Result<(), ()> {
     let clan = match client.clan_info(&clan_tag).await {
         Ok(clan) => clan,
@@ -537,7 +731,7 @@ pub async fn update_clan_games(
         }
     };
 
-    let season = Season::current();
+    let season = Season::current_with(clock);
 
     let clan_stats = storage.get_mut(clan_tag, &season).unwrap();
 
@@ -560,22 +754,134 @@ pub async fn update_clan_games(
                     achievement.value
                 );
 
-                let player_entry =
-                    clan_stats
-                        .games
-                        .entry(player_tag.clone())
-                        .or_insert(PlayerGamesStats {
-                            start_score: Some(achievement.value),
-                            end_score: achievement.value,
-                        });
-                if player_entry.start_score.is_none() {
-                    player_entry.start_score = Some(achievement.value);
+                if let Err(e) = clan_stats.ingest_games(player_tag.clone(), achievement.value) {
+                    tracing::error!("Updating Clan Games: {:?}", e);
+                    return Err(());
                 }
-
-                player_entry.end_score = achievement.value;
             }
         }
     }
+    clan_stats.touch(clock.now().into());
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raid_weekend_from_log() {
+        let raw = serde_json::json!({
+            "state": "ended",
+            "startTime": "20230804T070000.000Z",
+            "endTime": "20230807T070000.000Z",
+            "capitalTotalLoot": 9000,
+            "raidsCompleted": 1,
+            "totalAttacks": 3,
+            "enemyDistrictsDestroyed": 2,
+            "offensiveReward": 500,
+            "defensiveReward": 100,
+            "members": [
+                {
+                    "tag": "#2PP",
+                    "name": "First",
+                    "attacks": 2,
+                    "attackLimit": 5,
+                    "bonusAttackLimit": 1,
+                    "capitalResourcesLooted": 6000
+                },
+                {
+                    "tag": "#2QQ",
+                    "name": "Second",
+                    "attacks": 1,
+                    "attackLimit": 5,
+                    "bonusAttackLimit": 1,
+                    "capitalResourcesLooted": 3000
+                }
+            ],
+            "attackLog": [
+                {
+                    "defender": { "tag": "#9LL", "name": "Other", "level": 8 },
+                    "attackCount": 3,
+                    "districtCount": 2,
+                    "districtsDestroyed": 2,
+                    "districts": [
+                        {
+                            "id": 70000000,
+                            "name": "Capital Peak",
+                            "districtHallLevel": 8,
+                            "destructionPercent": 100,
+                            "stars": 3,
+                            "attackCount": 2,
+                            "totalLooted": 6000,
+                            "attacks": [
+                                {
+                                    "attacker": { "tag": "#2PP", "name": "First" },
+                                    "destructionPercent": 100,
+                                    "stars": 3
+                                },
+                                {
+                                    "attacker": { "tag": "#2PP", "name": "First" },
+                                    "destructionPercent": 60,
+                                    "stars": 1
+                                }
+                            ]
+                        },
+                        {
+                            "id": 70000001,
+                            "name": "Barbarian Camp",
+                            "districtHallLevel": 4,
+                            "destructionPercent": 100,
+                            "stars": 3,
+                            "attackCount": 1,
+                            "totalLooted": 3000,
+                            "attacks": [
+                                {
+                                    "attacker": { "tag": "#2QQ", "name": "Second" },
+                                    "destructionPercent": 100,
+                                    "stars": 3
+                                }
+                            ]
+                        }
+                    ]
+                }
+            ],
+            "defenseLog": []
+        });
+
+        let raid: CapitalRaidWeekend = serde_json::from_value(raw).unwrap();
+        let stats = raid.into_stats().unwrap();
+
+        let first = &stats.members[&PlayerTag("#2PP".to_string())];
+        assert_eq!(
+            (6000, 2, 6),
+            (first.looted, first.attacks_used, first.attacks_available)
+        );
+        assert_eq!(
+            vec![(100, 3), (60, 1)],
+            first
+                .attacks
+                .iter()
+                .map(|a| (a.destruction, a.stars))
+                .collect::<Vec<_>>()
+        );
+
+        let second = &stats.members[&PlayerTag("#2QQ".to_string())];
+        assert_eq!(
+            vec![RaidAttack {
+                defender: ClanTag("#9LL".to_string()),
+                district: "Barbarian Camp".to_string(),
+                district_hall_level: 4,
+                destruction: 100,
+                stars: 3,
+            }],
+            second.attacks
+        );
+
+        let mut clan = ClanStorage::default();
+        clan.ingest_raid(stats.clone()).unwrap();
+        clan.ingest_raid(stats).unwrap();
+        assert_eq!(1, clan.raid_weekend.len());
+    }
+}
@@ -0,0 +1,84 @@
+use std::{future::Future, time::Duration};
+
+use serde::Deserialize;
+
+use super::LoadError;
+
+/// The Time to wait between requesting two Pages, to stay within the Rate Limit of the API
+const PAGE_DELAY: Duration = Duration::from_millis(100);
+
+/// A single Page of a paginated Response from the API
+#[derive(Debug, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    #[serde(default)]
+    pub paging: Option<Paging>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Paging {
+    #[serde(default)]
+    pub cursors: Cursors,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Cursors {
+    pub after: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// The Cursor for the next Page, if there is one
+    fn next_cursor(&self) -> Option<&str> {
+        self.paging.as_ref()?.cursors.after.as_deref()
+    }
+}
+
+/// Requests every Page, starting without a Cursor and then following the `after` Cursor of
+/// each Page, and collects all the Items
+pub(crate) async fn collect_pages<T, F, Fut>(mut fetch: F) -> Result<Vec<T>, LoadError>
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: Future<Output = Result<Page<T>, LoadError>>,
+{
+    let mut items = Vec::new();
+    let mut cursor = None;
+
+    loop {
+        let page = fetch(cursor.take()).await?;
+        let next = page.next_cursor().map(|c| c.to_string());
+        items.extend(page.items);
+
+        match next {
+            Some(next) => {
+                cursor = Some(next);
+                tokio::time::sleep(PAGE_DELAY).await;
+            }
+            None => return Ok(items),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn collect_two_pages() {
+        let pages = [
+            r#"{ "items": [1, 2, 3], "paging": { "cursors": { "after": "abc" } } }"#,
+            r#"{ "items": [4, 5], "paging": { "cursors": { "before": "abc" } } }"#,
+        ];
+
+        let mut requested = Vec::new();
+        let items = collect_pages(|cursor: Option<String>| {
+            requested.push(cursor.clone());
+            let raw = pages[requested.len() - 1];
+            async move { Ok(serde_json::from_str::<Page<usize>>(raw).unwrap()) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(vec![1, 2, 3, 4, 5], items);
+        assert_eq!(vec![None, Some("abc".to_string())], requested);
+    }
+}
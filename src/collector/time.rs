@@ -7,6 +7,107 @@ pub struct Time {
     pub day: usize,
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub enum TimeError {
+    /// The Timestamp does not match the `YYYYMMDDTHHMMSS.mmmZ` format
+    InvalidFormat,
+    /// The named component is out of its valid range
+    InvalidComponent(&'static str),
+}
+
+impl std::fmt::Display for TimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidFormat => write!(f, "Expected a Timestamp like 20240315T120000.000Z"),
+            Self::InvalidComponent(name) => write!(f, "The {} is out of range", name),
+        }
+    }
+}
+
+impl std::error::Error for TimeError {}
+
+impl Time {
+    /// Parses a Timestamp as returned by the Clash of Clans API, like `20240315T120000.000Z`
+    pub fn from_coc_timestamp(raw: &str) -> Result<Self, TimeError> {
+        let bytes = raw.as_bytes();
+        if bytes.len() != 20 || bytes[8] != b'T' || bytes[15] != b'.' || bytes[19] != b'Z' {
+            return Err(TimeError::InvalidFormat);
+        }
+
+        let digits = [0..8, 9..15, 16..19];
+        if !digits
+            .into_iter()
+            .flatten()
+            .all(|idx| bytes[idx].is_ascii_digit())
+        {
+            return Err(TimeError::InvalidFormat);
+        }
+
+        // Only ASCII digits are left at these positions, so the String can be sliced and parsed
+        let component = |start: usize, end: usize| raw[start..end].parse::<usize>().unwrap();
+
+        let year = component(0, 4);
+        let month = component(4, 6);
+        let day = component(6, 8);
+
+        if !(1..=12).contains(&month) {
+            return Err(TimeError::InvalidComponent("month"));
+        }
+        if !(1..=31).contains(&day) {
+            return Err(TimeError::InvalidComponent("day"));
+        }
+        if component(9, 11) > 23 {
+            return Err(TimeError::InvalidComponent("hour"));
+        }
+        if component(11, 13) > 59 {
+            return Err(TimeError::InvalidComponent("minute"));
+        }
+        if component(13, 15) > 59 {
+            return Err(TimeError::InvalidComponent("second"));
+        }
+
+        Ok(Self { year, month, day })
+    }
+
+    /// The start of the Month, as a Day is the smallest valid Component it is set to the first
+    pub fn truncate_to_month(&self) -> Self {
+        Self {
+            year: self.year,
+            month: self.month,
+            day: 1,
+        }
+    }
+
+    /// The start of the Day, which is the Time itself as only the Date is tracked
+    pub fn truncate_to_day(&self) -> Self {
+        *self
+    }
+
+    /// The Date as a [`chrono::NaiveDate`], if the Day actually exists in the Month
+    pub fn to_date(&self) -> Option<chrono::NaiveDate> {
+        chrono::NaiveDate::from_ymd_opt(
+            i32::try_from(self.year).ok()?,
+            u32::try_from(self.month).ok()?,
+            u32::try_from(self.day).ok()?,
+        )
+    }
+}
+
+impl<T> From<chrono::DateTime<T>> for Time
+where
+    T: chrono::TimeZone,
+{
+    fn from(value: chrono::DateTime<T>) -> Self {
+        use chrono::Datelike;
+
+        Self {
+            year: value.year() as usize,
+            month: value.month() as usize,
+            day: value.day() as usize,
+        }
+    }
+}
+
 impl<'de> Deserialize<'de> for Time {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -14,19 +115,7 @@ impl<'de> Deserialize<'de> for Time {
     {
         let raw_value = String::deserialize(deserializer)?;
 
-        if raw_value.len() < 20 {
-            return Err(serde::de::Error::custom("".to_string()));
-        }
-
-        let raw_year = &raw_value[0..4];
-        let raw_month = &raw_value[4..6];
-        let raw_day = &raw_value[6..8];
-
-        let year: usize = raw_year.parse().map_err(|e| serde::de::Error::custom(e))?;
-        let month: usize = raw_month.parse().map_err(|e| serde::de::Error::custom(e))?;
-        let day: usize = raw_day.parse().map_err(|e| serde::de::Error::custom(e))?;
-
-        Ok(Self { year, month, day })
+        Self::from_coc_timestamp(&raw_value).map_err(serde::de::Error::custom)
     }
 }
 
@@ -79,6 +168,58 @@ mod tests {
         );
     }
 
+    #[test]
+    fn from_coc_timestamp() {
+        assert_eq!(
+            Ok(Time {
+                year: 2024,
+                month: 3,
+                day: 15,
+            }),
+            Time::from_coc_timestamp("20240315T120000.000Z")
+        );
+
+        assert_eq!(
+            Err(TimeError::InvalidFormat),
+            Time::from_coc_timestamp("2024-03-15T12:00:00Z")
+        );
+        assert_eq!(
+            Err(TimeError::InvalidFormat),
+            Time::from_coc_timestamp("20240315T12000.000Z")
+        );
+        assert_eq!(
+            Err(TimeError::InvalidComponent("month")),
+            Time::from_coc_timestamp("20241315T120000.000Z")
+        );
+        assert_eq!(
+            Err(TimeError::InvalidComponent("hour")),
+            Time::from_coc_timestamp("20240315T250000.000Z")
+        );
+    }
+
+    #[test]
+    fn truncate() {
+        let time = Time {
+            year: 2023,
+            month: 8,
+            day: 17,
+        };
+
+        assert_eq!(
+            Time {
+                year: 2023,
+                month: 8,
+                day: 1,
+            },
+            time.truncate_to_month()
+        );
+        assert_eq!(time, time.truncate_to_day());
+        assert_eq!(
+            time.truncate_to_month(),
+            time.truncate_to_month().truncate_to_day()
+        );
+    }
+
     #[test]
     fn ordering() {
         let first = Time {
@@ -1,7 +1,9 @@
 use reqwest::StatusCode;
 use serde::Deserialize;
 
-use crate::{ClanBadges, ClanTag, Client, LoadError, PlayerGamesStats, PlayerTag, Time, WarLog};
+use crate::{
+    ClanBadges, ClanTag, Client, LoadError, PlayerGamesStats, PlayerTag, Time, WarLog, WarResult,
+};
 
 pub struct WarClient<'c> {
     client: &'c Client,
@@ -17,8 +19,8 @@ impl<'c> WarClient<'c> {
             .client
             .client
             .get(format!(
-                "https://api.clashofclans.com/v1/clans/%23{}/warlog?limit=10",
-                clan.0.as_str().strip_prefix("#").unwrap_or(clan.0.as_str())
+                "https://api.clashofclans.com/v1/clans/{}/warlog?limit=10",
+                clan.url_encoded()
             ))
             .bearer_auth(&self.client.api_key)
             .send()
@@ -43,8 +45,8 @@ impl<'c> WarClient<'c> {
             .client
             .client
             .get(format!(
-                "https://api.clashofclans.com/v1/clans/%23{}/currentwar",
-                clan.0.as_str().strip_prefix("#").unwrap_or(clan.0.as_str())
+                "https://api.clashofclans.com/v1/clans/{}/currentwar",
+                clan.url_encoded()
             ))
             .bearer_auth(&self.client.api_key)
             .send()
@@ -70,11 +72,11 @@ impl<'c> WarClient<'c> {
 pub struct CurrentWar {
     pub state: CurrentWarState,
     pub clan: WarClan,
-    opponent: WarClan,
+    pub opponent: WarClan,
     #[serde(rename = "teamSize")]
     team_size: Option<usize>,
     #[serde(rename = "attacksPerMember")]
-    attacks_per_member: Option<usize>,
+    pub attacks_per_member: Option<usize>,
     #[serde(rename = "startTime")]
     pub start_time: Option<Time>,
     #[serde(rename = "endTime")]
@@ -83,6 +85,20 @@ pub struct CurrentWar {
     preparation_start_time: Option<String>,
 }
 
+impl CurrentWar {
+    /// The Result for our Clan, decided by the Stars and then by the Destruction
+    pub fn result(&self) -> WarResult {
+        let ours = (self.clan.stars, self.clan.destruction_percentage);
+        let theirs = (self.opponent.stars, self.opponent.destruction_percentage);
+
+        match ours.partial_cmp(&theirs) {
+            Some(std::cmp::Ordering::Greater) => WarResult::Win,
+            Some(std::cmp::Ordering::Less) => WarResult::Loss,
+            _ => WarResult::Tie,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub enum CurrentWarState {
     #[serde(rename = "clanNotFound")]
@@ -109,7 +125,7 @@ pub enum CurrentWarState {
 
 #[derive(Debug, Deserialize)]
 pub struct WarClan {
-    tag: Option<ClanTag>,
+    pub tag: Option<ClanTag>,
     name: Option<String>,
     attacks: usize,
     #[serde(rename = "badgeUrls")]
@@ -117,8 +133,8 @@ pub struct WarClan {
     #[serde(rename = "clanLevel")]
     clan_level: usize,
     #[serde(rename = "destructionPercentage")]
-    destruction_percentage: f32,
-    stars: usize,
+    pub destruction_percentage: f32,
+    pub stars: usize,
     #[serde(rename = "expEarned")]
     exp_earned: Option<f32>,
     pub members: Option<Vec<WarClanMember>>,
@@ -130,7 +146,7 @@ pub struct WarClanMember {
     name: String,
     opponentAttacks: usize,
     pub tag: PlayerTag,
-    townhallLevel: usize,
+    pub townhallLevel: usize,
     #[serde(default)]
     pub attacks: Vec<WarClanMemberAttack>,
     bestOpponentAttack: Option<serde_json::Value>,
@@ -0,0 +1,107 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+
+use crate::{ClanStorage, PlayerGamesStats, PlayerTag, RaidWeekendStats, Time, WarStats};
+
+/// Everything that changed in a [`ClanStorage`] compared to an earlier Snapshot of it
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct ClanStorageDelta {
+    /// The Wars that are new or were updated, by their Start Time
+    pub wars: BTreeMap<Time, WarStats>,
+    /// The Raid Weekends that are new or were updated, by their Start Time
+    pub raids: BTreeMap<Time, RaidWeekendStats>,
+    /// The current Clan Games Stats of every Player whose Stats changed
+    pub games: HashMap<PlayerTag, PlayerGamesStats>,
+    /// The Names of the Players that are new or were renamed
+    pub players: HashMap<PlayerTag, String>,
+}
+
+impl ClanStorageDelta {
+    pub fn is_empty(&self) -> bool {
+        self.wars.is_empty()
+            && self.raids.is_empty()
+            && self.games.is_empty()
+            && self.players.is_empty()
+    }
+}
+
+impl ClanStorage {
+    /// The Changes since the previous Snapshot of the same Clan and Season
+    pub fn delta_since(&self, previous: &ClanStorage) -> ClanStorageDelta {
+        ClanStorageDelta {
+            wars: self
+                .wars
+                .iter()
+                .filter(|(time, war)| previous.wars.get(time) != Some(war))
+                .map(|(time, war)| (*time, war.clone()))
+                .collect(),
+            raids: self
+                .raid_weekend
+                .iter()
+                .filter(|(time, raid)| previous.raid_weekend.get(time) != Some(raid))
+                .map(|(time, raid)| (*time, raid.clone()))
+                .collect(),
+            games: self
+                .games
+                .iter()
+                .filter(|(tag, stats)| previous.games.get(tag) != Some(stats))
+                .map(|(tag, stats)| (tag.clone(), stats.clone()))
+                .collect(),
+            players: self
+                .player_names
+                .iter()
+                .filter(|(tag, name)| previous.player_names.get(tag) != Some(name))
+                .map(|(tag, name)| (tag.clone(), name.clone()))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::MemberWarStats;
+
+    use super::*;
+
+    #[test]
+    fn new_war() {
+        let mut previous = ClanStorage::default();
+        previous
+            .player_names
+            .insert(PlayerTag("#2PP".to_string()), "Player".to_string());
+
+        let mut current = previous.clone();
+        assert!(current.delta_since(&previous).is_empty());
+
+        let start_time = Time {
+            year: 2023,
+            month: 8,
+            day: 4,
+        };
+        let war = WarStats {
+            start_time,
+            members: [(
+                PlayerTag("#2PP".to_string()),
+                MemberWarStats {
+                    attacks: Vec::new(),
+                },
+            )]
+            .into_iter()
+            .collect(),
+            attacks_per_member: 2,
+            opponent: None,
+            result: None,
+        };
+        current.ingest_war(war.clone()).unwrap();
+
+        let delta = current.delta_since(&previous);
+        assert_eq!(
+            ClanStorageDelta {
+                wars: [(start_time, war)].into_iter().collect(),
+                ..Default::default()
+            },
+            delta
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use crate::{ClanStorage, ClanTag, Clock, ScoreConfig, Season, Storage};
+
+/// The Overview of every Clan in a Season, highlighting what needs attention
+#[derive(Debug, Clone, PartialEq)]
+pub struct Digest {
+    pub season: Season,
+    /// The Digest of every Clan, ordered by Tag
+    pub clans: Vec<ClanDigest>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClanDigest {
+    pub clan: ClanTag,
+    /// The Percentage of the available War and CWL Attacks that were used
+    pub completion: f64,
+    /// The Number of Problems found by [`ClanStorage::validate`]
+    pub validation_issues: usize,
+    /// The Number of included Players that did not score any Points
+    pub inactive_players: usize,
+}
+
+impl ClanStorage {
+    /// The Percentage of the available War and CWL Attacks that were used, 100 if there were no
+    /// Attacks available
+    pub fn attack_completion(&self) -> f64 {
+        let (used, available) = self
+            .wars
+            .values()
+            .map(|war| (&war.members, war.attacks_per_member))
+            .chain(self.cwl.wars.iter().map(|war| (&war.members, 1)))
+            .flat_map(|(members, per_member)| {
+                members
+                    .values()
+                    .map(move |stats| (stats.attacks.len().min(per_member), per_member))
+            })
+            .fold((0, 0), |(used, available), (u, a)| {
+                (used + u, available + a)
+            });
+
+        if available == 0 {
+            return 100.0;
+        }
+
+        used as f64 / available as f64 * 100.0
+    }
+
+    fn digest(&self, clan: &ClanTag, cfg: &ScoreConfig) -> ClanDigest {
+        let included = self
+            .players_summary()
+            .filter(|(tag, _)| cfg.includes(tag))
+            .count();
+
+        ClanDigest {
+            clan: clan.clone(),
+            completion: self.attack_completion(),
+            validation_issues: self.validate().len(),
+            inactive_players: included - self.active_player_count(cfg),
+        }
+    }
+}
+
+impl Storage {
+    /// The Digest of the Season the Clock is currently in
+    pub fn digest(&self, cfg: &ScoreConfig, clock: &dyn Clock) -> Digest {
+        self.digest_for(&Season::current_with(clock), cfg)
+    }
+
+    /// The Digest of the given Season, including every registered Clan even if it has no Stats
+    /// for the Season yet
+    pub fn digest_for(&self, season: &Season, cfg: &ScoreConfig) -> Digest {
+        let empty = ClanStorage::default();
+
+        let mut clans: Vec<ClanDigest> = self
+            .clans()
+            .map(|clan| self.get(clan, season).unwrap_or(&empty).digest(clan, cfg))
+            .collect();
+        clans.sort_by(|a, b| a.clan.0.cmp(&b.clan.0));
+
+        Digest {
+            season: season.clone(),
+            clans,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::TimeZone;
+
+    use crate::{CwlWarStats, FixedClock, MemberWarStats, PlayerTag, Time, WarAttack, WarStats};
+
+    fn attack(stars: usize) -> WarAttack {
+        WarAttack {
+            destruction: 100,
+            stars,
+            duration: 100,
+            target: None,
+            order: 0,
+            attacker_townhall: None,
+            defender_townhall: None,
+        }
+    }
+
+    #[test]
+    fn digest() {
+        let active = ClanTag("#2PP".to_string());
+        let empty = ClanTag("#2QQ".to_string());
+        let season = Season::from_ymd(2023, 8).unwrap();
+        let tag = |raw: &str| PlayerTag(raw.to_string());
+
+        let mut storage = Storage::new_with_clans([empty.clone(), active.clone()]);
+        let stats = storage.get_mut(&active, &season).unwrap();
+        stats
+            .ingest_names([
+                (tag("#2RR"), "Attacker".to_string()),
+                (tag("#2YY"), "Inactive".to_string()),
+            ])
+            .unwrap();
+        stats
+            .ingest_war(WarStats {
+                start_time: Time {
+                    year: 2023,
+                    month: 8,
+                    day: 4,
+                },
+                members: [
+                    (
+                        tag("#2RR"),
+                        MemberWarStats {
+                            attacks: vec![attack(3), attack(2)],
+                        },
+                    ),
+                    (
+                        tag("#2YY"),
+                        MemberWarStats {
+                            attacks: Vec::new(),
+                        },
+                    ),
+                ]
+                .into_iter()
+                .collect(),
+                attacks_per_member: 2,
+                opponent: None,
+                result: None,
+            })
+            .unwrap();
+        // Recorded directly, as this is exactly the kind of Data validation should catch
+        stats.cwl.wars.push(CwlWarStats {
+            members: [(
+                tag("#2RR"),
+                MemberWarStats {
+                    attacks: vec![attack(3), attack(3)],
+                },
+            )]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        });
+
+        let digest = storage.digest_for(&season, &ScoreConfig::default());
+        assert_eq!(season, digest.season);
+        assert_eq!(
+            vec![
+                ClanDigest {
+                    clan: active,
+                    completion: 60.0,
+                    validation_issues: 1,
+                    inactive_players: 1,
+                },
+                ClanDigest {
+                    clan: empty,
+                    completion: 100.0,
+                    validation_issues: 0,
+                    inactive_players: 0,
+                },
+            ],
+            digest.clans
+        );
+
+        let end_of_season = FixedClock(
+            chrono::Utc
+                .with_ymd_and_hms(2023, 8, 31, 23, 59, 59)
+                .unwrap(),
+        );
+        assert_eq!(
+            digest,
+            storage.digest(&ScoreConfig::default(), &end_of_season)
+        );
+    }
+}
@@ -1,39 +1,91 @@
-use crate::ClanStorage;
+use crate::{ClanStorage, ExportManifest, ScoreConfig};
 
-pub struct ExcelStats {}
+pub struct ExcelStats {
+    /// The total Score a Player needs to qualify for the Gold-Pass
+    threshold: Option<f64>,
+    manifest: Option<ExportManifest>,
+}
 
 impl ExcelStats {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            threshold: None,
+            manifest: None,
+        }
+    }
+
+    /// Records the Season in the Properties of the Workbook and marks provisional Numbers in
+    /// the Header of the Worksheet
+    pub fn with_manifest(mut self, manifest: ExportManifest) -> Self {
+        self.manifest = Some(manifest);
+        self
+    }
+
+    /// Adds a Column marking whether each Player reached the given total Score
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = Some(threshold);
+        self
     }
 
     pub fn populate_workbook(&self, stats: &ClanStorage) -> rust_xlsxwriter::Workbook {
         let mut workbook = rust_xlsxwriter::Workbook::new();
+        if let Some(manifest) = self.manifest.as_ref() {
+            let status = if manifest.provisional {
+                "Provisional"
+            } else {
+                "Final"
+            };
+
+            workbook.set_properties(
+                &rust_xlsxwriter::DocProperties::new()
+                    .set_title(format!("Gold-Pass Tracking {}", manifest.season))
+                    .set_status(status),
+            );
+        }
+
         let worksheet = workbook.add_worksheet();
 
         worksheet.set_name("Gold-Pass Tracking").unwrap();
 
+        if let Some(manifest) = self.manifest.as_ref() {
+            if manifest.provisional {
+                worksheet.set_header("&CProvisional, the Season is still ongoing");
+            }
+        }
+
+        let score_config = stats.score_config(&ScoreConfig::default());
+
+        let order = stats.export_order();
+
         let mut column_index = (0..).into_iter();
 
         worksheet.write_string(0, column_index.next().unwrap(), "Name");
-        for idx in 1..8 {
+        for idx in 1..=order.cwl_wars.len().max(7) {
             worksheet.write_string(0, column_index.next().unwrap(), format!("CWL {}", idx));
         }
         worksheet.write_string(0, column_index.next().unwrap(), "CWL Score");
-        for idx in stats.wars.iter().enumerate().map(|(i, _)| i) {
+        for idx in 0..order.wars.len() {
             worksheet.write_string(0, column_index.next().unwrap(), format!("War {}", idx + 1));
         }
         worksheet.write_string(0, column_index.next().unwrap(), "War Score");
-        for idx in stats.raid_weekend.iter().enumerate().map(|(i, _)| i) {
+        for idx in 0..order.raids.len() {
             worksheet.write_string(0, column_index.next().unwrap(), format!("Raid {}", idx + 1));
         }
         worksheet.write_string(0, column_index.next().unwrap(), "Raid Score");
 
         worksheet.write_string(0, column_index.next().unwrap(), "Total Score");
+        if self.threshold.is_some() {
+            worksheet
+                .write_string(0, column_index.next().unwrap(), "Qualified")
+                .unwrap();
+        }
 
         let mut summaries: Vec<_> = stats
-            .players_summary()
-            .map(|(tag, sum)| (stats.player_names.get(&tag).unwrap(), tag, sum))
+            .weighted_summaries(&score_config)
+            .map(|(tag, sum)| {
+                let name = stats.player_names.get(&tag).cloned();
+                (name.unwrap_or_else(|| tag.0.clone()), tag, sum)
+            })
             .collect();
         summaries.sort_unstable_by(|(n, _, _), (n2, _, _)| n.cmp(n2));
 
@@ -41,15 +93,18 @@ impl ExcelStats {
             summaries.into_iter().enumerate().map(|(c, d)| (c + 1, d))
         {
             let row = row as u32;
+            let normalized = summary.normalized_with(&score_config);
+            let total = score_config.rounding.apply(normalized.total());
+            let normalized = normalized.rounded(score_config.rounding);
 
             let mut column_index = (0..).into_iter();
 
             worksheet
-                .write_string(row, column_index.next().unwrap(), name)
+                .write_string(row, column_index.next().unwrap(), &name)
                 .unwrap();
 
-            for w_index in 0..7 {
-                let stars = match stats.cwl.wars.get(w_index) {
+            for w_index in 0..order.cwl_wars.len().max(7) {
+                let stars = match order.cwl_wars.get(w_index) {
                     Some(war) => war
                         .members
                         .get(&tag)
@@ -60,48 +115,36 @@ impl ExcelStats {
 
                 worksheet.write_number(row, column_index.next().unwrap(), stars as f64);
             }
-            worksheet.write_number(
-                row,
-                column_index.next().unwrap(),
-                (summary.cwl_stars as f64 / 21.0) * 100.0,
-            );
+            worksheet.write_number(row, column_index.next().unwrap(), normalized.cwl);
 
-            // TODO
-            // Make sure this is actually sorted and not a random order
-            for (w_index, (_, w_stats)) in stats.wars.iter().enumerate() {
-                // TODO
+            for w_stats in order.wars.iter() {
                 let stars = w_stats
                     .members
                     .get(&tag)
-                    .map(|w| w.attacks.iter().map(|a| a.stars).sum())
+                    .map(|w| w.effective_stars())
                     .unwrap_or(0);
 
                 worksheet.write_number(row, column_index.next().unwrap(), stars as f64);
             }
-            worksheet.write_number(
-                row,
-                column_index.next().unwrap(),
-                (summary.war_stars as f64 / 66.0) * 100.0,
-            );
+            worksheet.write_number(row, column_index.next().unwrap(), normalized.war);
 
-            // TODO
-            // Make sure this is actually sorted by time
-            for (r_index, (d, raid)) in stats.raid_weekend.iter().enumerate() {
+            for raid in order.raids.iter() {
                 let loot = raid.members.get(&tag).map(|m| m.looted).unwrap_or(0);
 
                 worksheet.write_number(row, column_index.next().unwrap(), loot as f64);
             }
-            worksheet.write_number(
-                row,
-                column_index.next().unwrap(),
-                (summary.raid_loot as f64 / 120000.0) * 100.0,
-            );
-
-            let score = (summary.war_stars as f64 / 66.0) * 100.0
-                + (summary.cwl_stars as f64 / 21.0) * 100.0
-                + (summary.raid_loot as f64 / 120000.0) * 100.0
-                + (summary.games_score as f64 / 5000.0) * 100.0;
-            worksheet.write_number(row, column_index.next().unwrap(), score);
+            worksheet.write_number(row, column_index.next().unwrap(), normalized.raid);
+
+            worksheet.write_number(row, column_index.next().unwrap(), total);
+            if let Some(threshold) = self.threshold {
+                worksheet
+                    .write_boolean(
+                        row,
+                        column_index.next().unwrap(),
+                        summary.qualifies(&score_config, threshold),
+                    )
+                    .unwrap();
+            }
         }
 
         workbook
@@ -0,0 +1,309 @@
+use serde::Serialize;
+
+use crate::{
+    ClanStorage, ClanTag, Clock, CwlWarStats, PlayerTag, RaidWeekendStats, ScoreConfig, Season,
+    Storage, WarStats,
+};
+
+/// The Version of the [`SummaryExport`] Format, only increased for breaking Changes
+pub const SUMMARY_EXPORT_VERSION: u32 = 1;
+
+/// Describes the Data contained in an Export
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExportManifest {
+    pub season: Season,
+    /// The Season is still ongoing, so the exported Numbers are not final yet
+    pub provisional: bool,
+}
+
+impl ExportManifest {
+    pub fn new(season: Season, clock: &dyn Clock) -> Self {
+        let provisional = season.is_provisional(clock);
+        Self {
+            season,
+            provisional,
+        }
+    }
+}
+
+/// The Summary of a single Season of a Clan, independent of the internal Storage Format
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SummaryExport {
+    /// The Version of the Format, see [`SUMMARY_EXPORT_VERSION`]
+    pub version: u32,
+    pub clan: ClanTag,
+    pub season: Season,
+    /// All the Players ordered by their Score
+    pub players: Vec<PlayerExport>,
+}
+
+/// The Totals and Score of a single Player in a [`SummaryExport`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlayerExport {
+    pub tag: PlayerTag,
+    /// The Name of the Player, the Tag if the Name is not known
+    pub name: String,
+    pub war_stars: usize,
+    pub cwl_stars: usize,
+    pub raid_loot: usize,
+    pub games_score: usize,
+    /// The total Score using the default Score Config of the Clan
+    pub score: f64,
+}
+
+impl SummaryExport {
+    pub fn new(clan_storage: &ClanStorage, clan: ClanTag, season: Season) -> Self {
+        let cfg = ScoreConfig::default();
+        let rounding = clan_storage.score_config(&cfg).rounding;
+
+        let players = clan_storage
+            .leaderboard(&cfg)
+            .into_iter()
+            .map(|(tag, summary, score)| PlayerExport {
+                name: clan_storage
+                    .player_names
+                    .get(&tag)
+                    .cloned()
+                    .unwrap_or_else(|| tag.0.clone()),
+                tag,
+                war_stars: summary.war_stars,
+                cwl_stars: summary.cwl_stars,
+                raid_loot: summary.raid_loot,
+                games_score: summary.games_score,
+                score: rounding.apply(score),
+            })
+            .collect();
+
+        Self {
+            version: SUMMARY_EXPORT_VERSION,
+            clan,
+            season,
+            players,
+        }
+    }
+}
+
+/// The [`SummaryExport`] of the Season of the Clan as JSON, [`None`] if there are no Stats
+/// stored for it
+pub fn summary_json(storage: &Storage, clan: &ClanTag, season: &Season) -> Option<String> {
+    let clan_storage = storage.get(clan, season)?;
+    let export = SummaryExport::new(clan_storage, clan.clone(), season.clone());
+
+    Some(serde_json::to_string(&export).expect("The Export only contains serializable Data"))
+}
+
+/// The Wars and Raids of a Season in the Order they should be exported in
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportOrder<'s> {
+    /// The CWL Wars ordered by their Round
+    pub cwl_wars: Vec<&'s CwlWarStats>,
+    /// The regular Wars ordered by their Start Time
+    pub wars: Vec<&'s WarStats>,
+    /// The Raid Weekends ordered by their Start Time
+    pub raids: Vec<&'s RaidWeekendStats>,
+}
+
+impl ClanStorage {
+    /// All the Wars and Raids in chronological Order, which every Export should use instead of
+    /// iterating over the Stats directly
+    pub fn export_order(&self) -> ExportOrder<'_> {
+        let mut wars: Vec<&WarStats> = self.wars.values().collect();
+        wars.sort_by_key(|war| war.start_time);
+
+        let mut raids: Vec<&RaidWeekendStats> = self.raid_weekend.values().collect();
+        raids.sort_by_key(|raid| raid.start_time);
+
+        ExportOrder {
+            cwl_wars: self.cwl.sorted_wars(),
+            wars,
+            raids,
+        }
+    }
+}
+
+/// Quotes the Field if it contains a Separator, Quote or Line Break, doubling any Quotes in it
+fn csv_field(raw: &str) -> String {
+    if raw.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", raw.replace('"', "\"\""))
+    } else {
+        raw.to_string()
+    }
+}
+
+/// Formats the Leaderboard of the Clan as CSV, with a Header Row followed by one Row per Player
+/// ordered by their total Score
+pub fn leaderboard_csv(clan: &ClanStorage, cfg: &ScoreConfig) -> String {
+    let rounding = clan.score_config(cfg).rounding;
+
+    let mut csv = String::from("tag,name,war_stars,cwl_stars,raid_loot,games_score,total\n");
+    for (tag, summary, total) in clan.leaderboard(cfg) {
+        let name = clan.player_names.get(&tag).unwrap_or(&tag.0);
+
+        let row = [
+            csv_field(&tag.0),
+            csv_field(name),
+            summary.war_stars.to_string(),
+            summary.cwl_stars.to_string(),
+            summary.raid_loot.to_string(),
+            summary.games_score.to_string(),
+            format!("{:.2}", rounding.apply(total)),
+        ];
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    use crate::{FixedClock, MemberWarStats, PlayerGamesStats, PlayerTag, Time, WarTag};
+
+    #[test]
+    fn provisional() {
+        let clock = FixedClock(chrono::Utc.with_ymd_and_hms(2023, 8, 14, 12, 0, 0).unwrap());
+        let current = Season::current_with(&clock);
+        assert_eq!(Season::from_ymd(2023, 8).unwrap(), current);
+
+        let manifest = ExportManifest::new(current.clone(), &clock);
+        assert!(manifest.provisional);
+        assert_eq!(
+            serde_json::json!({ "season": "2023-08", "provisional": true }),
+            serde_json::to_value(&manifest).unwrap()
+        );
+
+        assert!(!ExportManifest::new(current.previous(), &clock).provisional);
+    }
+
+    #[test]
+    fn leaderboard_csv() {
+        let mut clan = ClanStorage::default();
+        for (tag, name, games) in [
+            ("#2PP", Some("Doe, \"JD\" John"), 1000),
+            ("#2QQ", None, 5000),
+        ] {
+            let tag = PlayerTag(tag.to_string());
+            if let Some(name) = name {
+                clan.player_names.insert(tag.clone(), name.to_string());
+            }
+            clan.games.insert(
+                tag,
+                PlayerGamesStats {
+                    start_score: Some(0),
+                    end_score: games,
+                },
+            );
+        }
+
+        assert_eq!(
+            "tag,name,war_stars,cwl_stars,raid_loot,games_score,total\n\
+            #2QQ,#2QQ,0,0,0,5000,100.00\n\
+            #2PP,\"Doe, \"\"JD\"\" John\",0,0,0,1000,20.00\n",
+            super::leaderboard_csv(&clan, &ScoreConfig::default())
+        );
+    }
+
+    #[test]
+    fn summary_json() {
+        let tag = ClanTag("#2PP".to_string());
+        let season = Season::from_ymd(2023, 8).unwrap();
+
+        let mut storage = Storage::new_with_clans([tag.clone()]);
+        let clan = storage.get_mut(&tag, &season).unwrap();
+        for (player, games) in [("#2QQ", 1000), ("#2RR", 5000)] {
+            let player = PlayerTag(player.to_string());
+            clan.player_names
+                .insert(player.clone(), format!("Name {}", player.0));
+            clan.games.insert(
+                player,
+                PlayerGamesStats {
+                    start_score: Some(0),
+                    end_score: games,
+                },
+            );
+        }
+
+        assert_eq!(
+            Some(
+                r##"{"version":1,"clan":"#2PP","season":"2023-08","players":[{"tag":"#2RR","name":"Name #2RR","war_stars":0,"cwl_stars":0,"raid_loot":0,"games_score":5000,"score":100.0},{"tag":"#2QQ","name":"Name #2QQ","war_stars":0,"cwl_stars":0,"raid_loot":0,"games_score":1000,"score":20.0}]}"##
+                    .to_string()
+            ),
+            super::summary_json(&storage, &tag, &season)
+        );
+        assert_eq!(
+            None,
+            super::summary_json(&storage, &ClanTag("#8YY".to_string()), &season)
+        );
+    }
+
+    #[test]
+    fn export_order() {
+        let time = |day| Time {
+            year: 2023,
+            month: 8,
+            day,
+        };
+
+        let mut clan = ClanStorage::default();
+        for day in [20, 4, 12] {
+            clan.ingest_war(WarStats {
+                start_time: time(day),
+                members: Default::default(),
+                attacks_per_member: 2,
+                opponent: None,
+                result: None,
+            })
+            .unwrap();
+            clan.ingest_raid(RaidWeekendStats {
+                start_time: time(day),
+                members: Default::default(),
+            })
+            .unwrap();
+        }
+        for round in [3, 0, 2, 1] {
+            clan.ingest_cwl_round(
+                WarTag(format!("#8QQ{}", round)),
+                round,
+                [(
+                    PlayerTag("#2PP".to_string()),
+                    MemberWarStats {
+                        attacks: Vec::new(),
+                    },
+                )]
+                .into_iter()
+                .collect(),
+            )
+            .unwrap();
+        }
+
+        let order = clan.export_order();
+        assert_eq!(
+            vec![4, 12, 20],
+            order
+                .wars
+                .iter()
+                .map(|war| war.start_time.day)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![4, 12, 20],
+            order
+                .raids
+                .iter()
+                .map(|raid| raid.start_time.day)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![0, 1, 2, 3],
+            order
+                .cwl_wars
+                .iter()
+                .map(|war| war.round)
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -4,12 +4,38 @@ pub use collector::*;
 mod tags;
 pub use tags::*;
 
+mod clock;
+pub use clock::*;
+
 mod ctracing;
 pub use ctracing::TracingCrateFilter;
 
 mod storage;
 pub use storage::*;
 
+mod score;
+pub use score::*;
+
+mod validation;
+pub use validation::*;
+
+mod participation;
+pub use participation::*;
+
+mod digest;
+pub use digest::*;
+
+mod delta;
+pub use delta::*;
+
+mod table;
+
+#[cfg(feature = "parquet")]
+mod parquetexport;
+
+mod export;
+pub use export::*;
+
 mod excelstats;
 pub use excelstats::ExcelStats;
 
@@ -29,8 +55,10 @@ pub fn parse_storage(args: &str) -> Result<Box<dyn StorageBackend>, &'static str
                 let s3_secret_key =
                     std::env::var("S3_SECRET_KEY").expect("Missing `S3_SECRET_KEY`");
                 let s3_endpoint = std::env::var("S3_ENDPOINT").expect("Missing `S3_ENDPOINT`");
+                let s3_filename =
+                    std::env::var("S3_FILENAME").unwrap_or_else(|_| "storage.json".to_string());
 
-                Some(Box::new(S3Storage::new(
+                Some(Box::new(S3Storage::with_filename(
                     s3::Bucket::new(
                         &s3_bucket,
                         s3::Region::Custom {
@@ -48,6 +76,7 @@ pub fn parse_storage(args: &str) -> Result<Box<dyn StorageBackend>, &'static str
                     )
                     .unwrap()
                     .with_path_style(),
+                    s3_filename,
                 )))
             }
             other => {
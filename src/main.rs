@@ -7,7 +7,7 @@ use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use gold_pass_bot::{
-    ClanTag, ExcelStats, PlayerSummary, RaidMember, RaidWeekendStats, Season, Storage,
+    ClanTag, ExcelStats, ExportManifest, PlayerSummary, Season, Storage, StorageError, SystemClock,
 };
 use serenity::async_trait;
 use serenity::framework::standard::macros::{command, group};
@@ -85,9 +85,13 @@ async fn main() {
         .duration_since(UNIX_EPOCH)
         .unwrap()
         .as_secs();
-    let storage = Storage::load(storage_backend.as_mut())
-        .await
-        .unwrap_or_else(|_| Storage::empty());
+    // Only a missing Storage starts fresh, anything else would overwrite the actual Data with
+    // an empty Storage on the next Save
+    let storage = match Storage::load(storage_backend.as_mut()).await {
+        Ok(storage) => storage,
+        Err(StorageError::NotFound) => Storage::empty(),
+        Err(e) => panic!("Loading Storage: {}", e),
+    };
     let shared_storage = Arc::new(ArcSwap::new(Arc::new((storage.clone(), elapsed))));
     {
         let mut data = client.data.write().await;
@@ -141,64 +145,31 @@ async fn main() {
                     error_counter.inc();
                 }
 
-                if let Err(e) = gold_pass_bot::update_war(&client, &tag, &mut storage).await {
+                if let Err(e) =
+                    gold_pass_bot::update_war(&client, &tag, &mut storage, &SystemClock).await
+                {
                     error_counter.inc();
                 }
 
-                if let Err(e) = gold_pass_bot::update_cwl(&client, &tag, &mut storage).await {
+                if let Err(e) =
+                    gold_pass_bot::update_cwl(&client, &tag, &mut storage, &SystemClock).await
+                {
                     error_counter.inc();
                 }
 
-                if let Err(e) = gold_pass_bot::update_clan_games(&client, &tag, &mut storage).await
+                if let Err(e) =
+                    gold_pass_bot::update_clan_games(&client, &tag, &mut storage, &SystemClock)
+                        .await
                 {
                     error_counter.inc();
                 }
 
-                match client.captial_raid_seasons(&tag).await {
-                    Ok(raid_res) => {
-                        for raid in raid_res.items {
-                            tracing::debug!("Start-Time: {:?}", raid.startTime);
-                            let members = match raid.members {
-                                Some(m) => m
-                                    .into_iter()
-                                    .map(|member| {
-                                        (
-                                            member.tag,
-                                            RaidMember {
-                                                looted: member.capitalResourcesLooted,
-                                            },
-                                        )
-                                    })
-                                    .collect(),
-                                None => {
-                                    tracing::trace!(
-                                        "Skipping weekend, because there is no member list"
-                                    );
-
-                                    continue;
-                                }
-                            };
-
-                            let start_time = raid.startTime;
-
-                            let clan_season_stats =
-                                storage.get_mut(&tag, &raid.startTime.into()).unwrap();
-
-                            clan_season_stats.raid_weekend.insert(
-                                start_time.clone(),
-                                RaidWeekendStats {
-                                    start_time,
-                                    members,
-                                },
-                            );
-                        }
-                    }
-                    Err(e) => {
-                        error_counter.inc();
-
-                        tracing::error!("Error loading Capital Raid Seasons: {:?}", e);
-                    }
-                };
+                if gold_pass_bot::update_raids(&client, &tag, &mut storage, &SystemClock)
+                    .await
+                    .is_err()
+                {
+                    error_counter.inc();
+                }
 
                 drop(_tmp);
             }
@@ -207,12 +178,12 @@ async fn main() {
                 .duration_since(UNIX_EPOCH)
                 .unwrap()
                 .as_secs();
-            shared_storage.swap(Arc::new((storage.clone(), elapsed)));
-
-            if let Err(e) = storage.save(storage_backend.as_mut()).await {
+            if let Err(e) = storage.save_merging(storage_backend.as_mut()).await {
                 tracing::error!("Saving Storage: {:?}", e);
             }
 
+            shared_storage.swap(Arc::new((storage.clone(), elapsed)));
+
             tracing::info!("Done Updating Stats");
 
             tokio::time::sleep(Duration::from_secs(90)).await;
@@ -238,7 +209,7 @@ fn generate_batches(
     player_count: usize,
     header_padding_width: usize,
     padding_width: usize,
-    summaries: BTreeMap<&String, PlayerSummary>,
+    summaries: BTreeMap<String, PlayerSummary>,
     timestamp: u64,
 ) -> Vec<String> {
     let mut summary_iter = summaries
@@ -320,7 +291,7 @@ async fn stats(ctx: &Context, msg: &Message) -> CommandResult {
                 .player_names
                 .get(&n)
                 .map(|n| n.len())
-                .unwrap_or(0)
+                .unwrap_or(n.0.len())
         })
         .max()
         .unwrap_or(0);
@@ -330,7 +301,10 @@ async fn stats(ctx: &Context, msg: &Message) -> CommandResult {
 
     let player_summaries: BTreeMap<_, _> = clan_stats
         .players_summary()
-        .map(|(tag, v)| (clan_stats.player_names.get(&tag).unwrap(), v))
+        .map(|(tag, v)| {
+            let name = clan_stats.player_names.get(&tag).cloned();
+            (name.unwrap_or(tag.0), v)
+        })
         .collect();
 
     if let Err(e) = msg
@@ -390,7 +364,9 @@ async fn export(ctx: &Context, msg: &Message) -> CommandResult {
     let files = [current_season, last_season].map(|season| {
         let clan_stats = stats.get(&alfie_tag, &season).expect("");
 
-        let mut excel_book = ExcelStats::new().populate_workbook(clan_stats);
+        let mut excel_book = ExcelStats::new()
+            .with_manifest(ExportManifest::new(season.clone(), &SystemClock))
+            .populate_workbook(clan_stats);
         let content = excel_book.save_to_buffer().unwrap();
 
         AttachmentType::Bytes {
@@ -0,0 +1,147 @@
+use std::{path::Path, sync::Arc};
+
+use arrow_array::{ArrayRef, Float64Array, RecordBatch, StringArray, UInt64Array};
+use arrow_schema::{DataType, Field, Schema};
+use parquet::{arrow::ArrowWriter, errors::ParquetError};
+
+use crate::{ClanStorage, ScoreConfig};
+
+impl ClanStorage {
+    /// Writes one Row per Player, with the raw Stats and the normalized Scores, to a Parquet File
+    /// at the given Path. The Rows are ordered by the total Score and then by the Tag
+    pub fn to_parquet(&self, path: impl AsRef<Path>) -> Result<(), ParquetError> {
+        let cfg = self.score_config(&ScoreConfig::default());
+
+        let mut rows: Vec<_> = self
+            .weighted_summaries(&cfg)
+            .map(|(tag, summary)| {
+                let normalized = summary.normalized_with(&cfg);
+                (tag, summary, normalized)
+            })
+            .collect();
+        rows.sort_by(|(t1, _, n1), (t2, _, n2)| {
+            n2.total()
+                .total_cmp(&n1.total())
+                .then_with(|| t1.0.cmp(&t2.0))
+        });
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("tag", DataType::Utf8, false),
+            Field::new("name", DataType::Utf8, true),
+            Field::new("war_stars", DataType::UInt64, false),
+            Field::new("cwl_stars", DataType::UInt64, false),
+            Field::new("raid_loot", DataType::UInt64, false),
+            Field::new("games_score", DataType::UInt64, false),
+            Field::new("war_score", DataType::Float64, false),
+            Field::new("cwl_score", DataType::Float64, false),
+            Field::new("raid_score", DataType::Float64, false),
+            Field::new("games_score_normalized", DataType::Float64, false),
+            Field::new("total", DataType::Float64, false),
+        ]));
+
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|(tag, _, _)| tag.0.as_str()),
+            )),
+            Arc::new(StringArray::from_iter(rows.iter().map(|(tag, _, _)| {
+                self.player_names.get(tag).map(|name| name.as_str())
+            }))),
+            Arc::new(UInt64Array::from_iter_values(
+                rows.iter().map(|(_, summary, _)| summary.war_stars as u64),
+            )),
+            Arc::new(UInt64Array::from_iter_values(
+                rows.iter().map(|(_, summary, _)| summary.cwl_stars as u64),
+            )),
+            Arc::new(UInt64Array::from_iter_values(
+                rows.iter().map(|(_, summary, _)| summary.raid_loot as u64),
+            )),
+            Arc::new(UInt64Array::from_iter_values(
+                rows.iter()
+                    .map(|(_, summary, _)| summary.games_score as u64),
+            )),
+            Arc::new(Float64Array::from_iter_values(
+                rows.iter().map(|(_, _, normalized)| normalized.war),
+            )),
+            Arc::new(Float64Array::from_iter_values(
+                rows.iter().map(|(_, _, normalized)| normalized.cwl),
+            )),
+            Arc::new(Float64Array::from_iter_values(
+                rows.iter().map(|(_, _, normalized)| normalized.raid),
+            )),
+            Arc::new(Float64Array::from_iter_values(
+                rows.iter().map(|(_, _, normalized)| normalized.games),
+            )),
+            Arc::new(Float64Array::from_iter_values(
+                rows.iter().map(|(_, _, normalized)| normalized.total()),
+            )),
+        ];
+        let batch = RecordBatch::try_new(schema.clone(), columns)?;
+
+        let file = std::fs::File::create(path)?;
+        let mut writer = ArrowWriter::try_new(file, schema, None)?;
+        writer.write(&batch)?;
+        writer.close()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::{Array, Float64Array, StringArray, UInt64Array};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use crate::{ClanStorage, PlayerGamesStats, PlayerTag};
+
+    #[test]
+    fn to_parquet() {
+        let mut clan = ClanStorage::default();
+        for (tag, games) in [("#2PP", 1000), ("#2QQ", 5000), ("#2RR", 0)] {
+            let tag = PlayerTag(tag.to_string());
+            clan.player_names
+                .insert(tag.clone(), format!("Name {}", tag.0));
+            clan.games.insert(
+                tag,
+                PlayerGamesStats {
+                    start_score: Some(0),
+                    end_score: games,
+                },
+            );
+        }
+
+        let path =
+            std::env::temp_dir().join(format!("gold-pass-parquet-{}.parquet", std::process::id()));
+        clan.to_parquet(&path).unwrap();
+
+        let file = std::fs::File::open(&path).unwrap();
+        let batches: Vec<_> = ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .build()
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(1, batches.len());
+        let batch = &batches[0];
+        assert_eq!(3, batch.num_rows());
+
+        let column = |name: &str| batch.column_by_name(name).unwrap().clone();
+        let tags = column("tag");
+        let tags = tags.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(
+            vec!["#2QQ", "#2PP", "#2RR"],
+            tags.iter().map(|tag| tag.unwrap()).collect::<Vec<_>>()
+        );
+
+        let names = column("name");
+        let names = names.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!("Name #2PP", names.value(1));
+        let games = column("games_score");
+        let games = games.as_any().downcast_ref::<UInt64Array>().unwrap();
+        assert_eq!(1000, games.value(1));
+        let total = column("total");
+        let total = total.as_any().downcast_ref::<Float64Array>().unwrap();
+        assert!((total.value(1) - 20.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,463 @@
+use crate::{ClanStorage, PlayerTag, Time};
+
+/// The fraction of the Roster participating in each Category
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParticipationRates {
+    /// Players with at least one regular or CWL War Attack
+    pub war: f64,
+    /// Players that looted during at least one Raid Weekend
+    pub raid: f64,
+    /// Players that earned Clan Games Points
+    pub games: f64,
+}
+
+/// The Participation of a single Player across the Season
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParticipationReport {
+    /// The regular Wars in which the Player attacked at least once
+    pub wars_participated: usize,
+    /// The regular Wars the Player was part of
+    pub wars_available: usize,
+    /// The Attacks of the Player across all the CWL Rounds
+    pub cwl_attacks: usize,
+    /// The Raid Weekends during which the Player looted any Capital Gold
+    pub raids_with_loot: usize,
+    /// Whether the Player earned any Clan Games Points
+    pub games: bool,
+}
+
+impl ClanStorage {
+    /// The participation of the Roster, all the Players with a known name, in the Season
+    pub fn participation_rates(&self) -> ParticipationRates {
+        let roster: Vec<&PlayerTag> = self.player_names.keys().collect();
+        if roster.is_empty() {
+            return ParticipationRates {
+                war: 0.0,
+                raid: 0.0,
+                games: 0.0,
+            };
+        }
+
+        let rate = |predicate: &dyn Fn(&PlayerTag) -> bool| {
+            roster.iter().filter(|tag| predicate(tag)).count() as f64 / roster.len() as f64
+        };
+
+        let war = rate(&|tag| {
+            self.wars
+                .values()
+                .map(|war| &war.members)
+                .chain(self.cwl.wars.iter().map(|war| &war.members))
+                .any(|members| {
+                    members
+                        .get(tag)
+                        .map(|stats| !stats.attacks.is_empty())
+                        .unwrap_or(false)
+                })
+        });
+        let raid = rate(&|tag| {
+            self.raid_weekend.values().any(|raid| {
+                raid.members
+                    .get(tag)
+                    .map(|member| member.looted > 0)
+                    .unwrap_or(false)
+            })
+        });
+        let games = rate(&|tag| {
+            self.games
+                .get(tag)
+                .map(|games| games.end_score > games.start_score.unwrap_or(games.end_score))
+                .unwrap_or(false)
+        });
+
+        ParticipationRates { war, raid, games }
+    }
+
+    /// The Participation of the Player across the Season, all zeros for a Player without any
+    /// recorded Stats
+    pub fn participation(&self, player: &PlayerTag) -> ParticipationReport {
+        let wars: Vec<_> = self
+            .wars
+            .values()
+            .filter_map(|war| war.members.get(player))
+            .collect();
+
+        ParticipationReport {
+            wars_participated: wars
+                .iter()
+                .filter(|stats| !stats.attacks.is_empty())
+                .count(),
+            wars_available: wars.len(),
+            cwl_attacks: self
+                .cwl
+                .wars
+                .iter()
+                .filter_map(|war| war.members.get(player))
+                .map(|stats| stats.attacks.len())
+                .sum(),
+            raids_with_loot: self
+                .raid_weekend
+                .values()
+                .filter_map(|raid| raid.members.get(player))
+                .filter(|member| member.looted > 0)
+                .count(),
+            games: self
+                .games
+                .get(player)
+                .map(|games| games.end_score > games.start_score.unwrap_or(games.end_score))
+                .unwrap_or(false),
+        }
+    }
+
+    /// The Number of Raid Weekends recorded in the Season
+    pub fn raid_weekend_count(&self) -> usize {
+        self.raid_weekend.len()
+    }
+
+    /// The Capital Gold looted per Raid Attack by the Player over the Season, [`None`] if the
+    /// Player did not attack at all. Weekends recorded before the used Attacks were tracked are
+    /// skipped, as their Loot can not be attributed to any Attacks
+    pub fn raid_efficiency(&self, player: &PlayerTag) -> Option<f64> {
+        let (looted, attacks) = self
+            .raid_weekend
+            .values()
+            .filter_map(|raid| raid.members.get(player))
+            .filter(|member| member.attacks_used > 0)
+            .fold((0usize, 0usize), |(looted, attacks), member| {
+                (
+                    looted.saturating_add(member.looted),
+                    attacks.saturating_add(member.attacks_used),
+                )
+            });
+
+        if attacks == 0 {
+            return None;
+        }
+
+        Some(looted as f64 / attacks as f64)
+    }
+
+    /// Every Raid Weekend ordered by Time, together with each Participant, ordered by Tag, and
+    /// the Capital Gold they looted
+    pub fn raid_participation_matrix(&self) -> Vec<(Time, Vec<(PlayerTag, usize)>)> {
+        self.raid_weekend
+            .iter()
+            .map(|(time, raid)| {
+                let mut members: Vec<(PlayerTag, usize)> = raid
+                    .members
+                    .iter()
+                    .map(|(tag, member)| (tag.clone(), member.looted))
+                    .collect();
+                members.sort_by(|(a, _), (b, _)| a.0.cmp(&b.0));
+
+                (*time, members)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        MemberWarStats, PlayerGamesStats, RaidMember, RaidWeekendStats, WarAttack, WarStats, WarTag,
+    };
+
+    #[test]
+    fn participation_rates() {
+        let tags: Vec<PlayerTag> = ["#2PP", "#2QQ", "#2RR", "#2YY"]
+            .into_iter()
+            .map(|tag| PlayerTag(tag.to_string()))
+            .collect();
+        let start_time = Time {
+            year: 2023,
+            month: 8,
+            day: 4,
+        };
+
+        let mut clan = ClanStorage::default();
+        for tag in tags.iter() {
+            clan.player_names.insert(tag.clone(), tag.0.clone());
+        }
+
+        clan.ingest_war(WarStats {
+            start_time,
+            attacks_per_member: 2,
+            opponent: None,
+            result: None,
+            members: [
+                (
+                    tags[0].clone(),
+                    MemberWarStats {
+                        attacks: vec![WarAttack {
+                            destruction: 100,
+                            stars: 3,
+                            duration: 100,
+                            target: None,
+                            order: 1,
+                            attacker_townhall: None,
+                            defender_townhall: None,
+                        }],
+                    },
+                ),
+                (
+                    tags[1].clone(),
+                    MemberWarStats {
+                        attacks: Vec::new(),
+                    },
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        })
+        .unwrap();
+        clan.ingest_raid(RaidWeekendStats {
+            start_time,
+            members: [
+                (
+                    tags[0].clone(),
+                    RaidMember {
+                        looted: 1000,
+                        attacks_used: 0,
+                        attacks_available: 0,
+                        attacks: Vec::new(),
+                    },
+                ),
+                (
+                    tags[1].clone(),
+                    RaidMember {
+                        looted: 2000,
+                        attacks_used: 0,
+                        attacks_available: 0,
+                        attacks: Vec::new(),
+                    },
+                ),
+                (
+                    tags[2].clone(),
+                    RaidMember {
+                        looted: 3000,
+                        attacks_used: 0,
+                        attacks_available: 0,
+                        attacks: Vec::new(),
+                    },
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        })
+        .unwrap();
+        clan.ingest_games(tags[3].clone(), 0).unwrap();
+        clan.ingest_games(tags[3].clone(), 1000).unwrap();
+
+        assert_eq!(
+            ParticipationRates {
+                war: 0.25,
+                raid: 0.75,
+                games: 0.25,
+            },
+            clan.participation_rates()
+        );
+    }
+
+    #[test]
+    fn raid_participation_matrix() {
+        let tag = |raw: &str| PlayerTag(raw.to_string());
+        let first = Time {
+            year: 2023,
+            month: 8,
+            day: 4,
+        };
+        let second = Time {
+            year: 2023,
+            month: 8,
+            day: 11,
+        };
+
+        let mut clan = ClanStorage::default();
+        // Ingested out of order to make sure the Weekends are still sorted
+        clan.ingest_raid(RaidWeekendStats {
+            start_time: second,
+            members: [
+                (
+                    tag("#2RR"),
+                    RaidMember {
+                        looted: 3000,
+                        attacks_used: 0,
+                        attacks_available: 0,
+                        attacks: Vec::new(),
+                    },
+                ),
+                (
+                    tag("#2QQ"),
+                    RaidMember {
+                        looted: 2500,
+                        attacks_used: 0,
+                        attacks_available: 0,
+                        attacks: Vec::new(),
+                    },
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        })
+        .unwrap();
+        clan.ingest_raid(RaidWeekendStats {
+            start_time: first,
+            members: [
+                (
+                    tag("#2QQ"),
+                    RaidMember {
+                        looted: 2000,
+                        attacks_used: 0,
+                        attacks_available: 0,
+                        attacks: Vec::new(),
+                    },
+                ),
+                (
+                    tag("#2PP"),
+                    RaidMember {
+                        looted: 1000,
+                        attacks_used: 0,
+                        attacks_available: 0,
+                        attacks: Vec::new(),
+                    },
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        })
+        .unwrap();
+
+        assert_eq!(2, clan.raid_weekend_count());
+        assert_eq!(
+            vec![
+                (first, vec![(tag("#2PP"), 1000), (tag("#2QQ"), 2000)]),
+                (second, vec![(tag("#2QQ"), 2500), (tag("#2RR"), 3000)]),
+            ],
+            clan.raid_participation_matrix()
+        );
+    }
+
+    #[test]
+    fn raid_efficiency() {
+        let tag = |raw: &str| PlayerTag(raw.to_string());
+        let member = |looted, attacks_used| RaidMember {
+            looted,
+            attacks_used,
+            attacks_available: 6,
+            attacks: Vec::new(),
+        };
+
+        let mut clan = ClanStorage::default();
+        // The last Weekend was recorded before the used Attacks were tracked
+        for (day, efficient, spammer) in [
+            (4, member(6000, 5), member(3000, 6)),
+            (11, member(6000, 5), member(0, 0)),
+            (18, member(9000, 0), member(4000, 0)),
+        ] {
+            clan.ingest_raid(RaidWeekendStats {
+                start_time: Time {
+                    year: 2023,
+                    month: 8,
+                    day,
+                },
+                members: [(tag("#2PP"), efficient), (tag("#2QQ"), spammer)]
+                    .into_iter()
+                    .collect(),
+            })
+            .unwrap();
+        }
+
+        assert_eq!(Some(1200.0), clan.raid_efficiency(&tag("#2PP")));
+        assert_eq!(Some(500.0), clan.raid_efficiency(&tag("#2QQ")));
+        assert_eq!(None, clan.raid_efficiency(&tag("#2RR")));
+    }
+
+    #[test]
+    fn participation() {
+        let player = PlayerTag("#2PP".to_string());
+        let attack = |order| WarAttack {
+            destruction: 100,
+            stars: 3,
+            duration: 100,
+            target: None,
+            order,
+            attacker_townhall: None,
+            defender_townhall: None,
+        };
+
+        let mut clan = ClanStorage::default();
+        for (day, attacks) in [(4, vec![attack(1), attack(2)]), (6, Vec::new())] {
+            clan.ingest_war(WarStats {
+                start_time: Time {
+                    year: 2023,
+                    month: 8,
+                    day,
+                },
+                attacks_per_member: 2,
+                opponent: None,
+                result: None,
+                members: [(player.clone(), MemberWarStats { attacks })]
+                    .into_iter()
+                    .collect(),
+            })
+            .unwrap();
+        }
+        for round in 0..2 {
+            clan.ingest_cwl_round(
+                WarTag(format!("#8QQ{}", round)),
+                round,
+                [(
+                    player.clone(),
+                    MemberWarStats {
+                        attacks: vec![attack(1)],
+                    },
+                )]
+                .into_iter()
+                .collect(),
+            )
+            .unwrap();
+        }
+        clan.ingest_raid(RaidWeekendStats {
+            start_time: Time {
+                year: 2023,
+                month: 8,
+                day: 11,
+            },
+            members: [(
+                player.clone(),
+                RaidMember {
+                    looted: 3000,
+                    attacks_used: 6,
+                    attacks_available: 6,
+                    attacks: Vec::new(),
+                },
+            )]
+            .into_iter()
+            .collect(),
+        })
+        .unwrap();
+        clan.games.insert(
+            player.clone(),
+            PlayerGamesStats {
+                start_score: Some(1000),
+                end_score: 5000,
+            },
+        );
+
+        assert_eq!(
+            ParticipationReport {
+                wars_participated: 1,
+                wars_available: 2,
+                cwl_attacks: 2,
+                raids_with_loot: 1,
+                games: true,
+            },
+            clan.participation(&player)
+        );
+        assert_eq!(
+            ParticipationReport::default(),
+            clan.participation(&PlayerTag("#2QQ".to_string()))
+        );
+    }
+}
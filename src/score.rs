@@ -0,0 +1,715 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{ser::SerializeStruct, Deserialize, Serialize};
+
+use crate::{ClanStorage, PlayerSummary, PlayerTag, RaidMember, CWL_MAX_STARS_PER_ROUND};
+
+mod json;
+pub use json::{JsonScorer, JsonScorerError, WeightedCategory};
+
+/// The CWL Stars needed for the full Score in a regular Season with 7 Rounds
+const DEFAULT_CWL_STAR_CAP: usize = 7 * CWL_MAX_STARS_PER_ROUND;
+
+/// The Configuration used to turn the raw Stats of a Player into a Score
+#[derive(Debug, Clone)]
+pub struct ScoreConfig {
+    /// The Number of War Stars needed for the full 100 Points
+    pub war_star_cap: usize,
+    /// The Number of CWL Stars needed for the full 100 Points, if not set this is based on
+    /// the Number of Rounds the Clan actually played (see [`ClanStorage::cwl_stars_max`])
+    pub cwl_star_cap: Option<usize>,
+    /// The amount of Capital Gold needed for the full 100 Points
+    pub capital_gold_cap: usize,
+    /// The Clan Games Score needed for the full 100 Points
+    pub games_cap: usize,
+    /// Players that should not be considered when scoring a Clan
+    pub excluded: HashSet<PlayerTag>,
+    /// If set, only these Players are considered when scoring a Clan, Players in
+    /// [`ScoreConfig::excluded`] are still left out
+    pub only: Option<HashSet<PlayerTag>>,
+    /// How the Scores are rounded when presented, the Scores themselves are always calculated
+    /// with full precision
+    pub rounding: Rounding,
+    /// The factor applied to Capital Gold looted in a District, by the Name of the District,
+    /// Districts without an entry use a factor of 1
+    pub district_multipliers: HashMap<String, f64>,
+}
+
+impl Default for ScoreConfig {
+    fn default() -> Self {
+        Self {
+            war_star_cap: 66,
+            cwl_star_cap: None,
+            capital_gold_cap: 120000,
+            games_cap: 5000,
+            excluded: HashSet::new(),
+            only: None,
+            rounding: Rounding::default(),
+            district_multipliers: HashMap::new(),
+        }
+    }
+}
+
+/// How a Score is rounded for presentation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// Rounds down to the next whole Number
+    Floor,
+    /// Rounds to the nearest whole Number
+    Nearest,
+    /// Rounds to the nearest Number with the given Number of Decimals
+    Decimals(u32),
+}
+
+impl Default for Rounding {
+    fn default() -> Self {
+        Self::Decimals(2)
+    }
+}
+
+impl Rounding {
+    pub fn apply(&self, score: f64) -> f64 {
+        match self {
+            Self::Floor => score.floor(),
+            Self::Nearest => score.round(),
+            Self::Decimals(decimals) => {
+                let factor = 10f64.powi(*decimals as i32);
+                (score * factor).round() / factor
+            }
+        }
+    }
+}
+
+impl ScoreConfig {
+    /// Whether or not the Player should be considered for Scoring
+    pub fn includes(&self, player: &PlayerTag) -> bool {
+        !self.excluded.contains(player)
+            && self.only.as_ref().is_none_or(|only| only.contains(player))
+    }
+
+    /// The factor applied to Capital Gold looted in the District
+    pub fn district_multiplier(&self, district: &str) -> f64 {
+        self.district_multipliers
+            .get(district)
+            .copied()
+            .unwrap_or(1.0)
+    }
+
+    /// The Capital Gold looted by the Member, weighted by the Districts they attacked. As the
+    /// API does not report the Loot of every single Attack, the Loot is split evenly across the
+    /// Attacks of the Member
+    fn weighted_loot(&self, member: &RaidMember) -> f64 {
+        if member.attacks.is_empty() {
+            return member.looted as f64;
+        }
+
+        let multipliers: f64 = member
+            .attacks
+            .iter()
+            .map(|attack| self.district_multiplier(&attack.district))
+            .sum();
+        member.looted as f64 * multipliers / member.attacks.len() as f64
+    }
+}
+
+/// Turns the Summary of a Player into a single Score
+pub trait Scorer {
+    fn score(&self, summary: &PlayerSummary) -> f64;
+}
+
+impl Scorer for ScoreConfig {
+    fn score(&self, summary: &PlayerSummary) -> f64 {
+        summary.total_score_with(self)
+    }
+}
+
+/// Normalizes the value against the cap to a Score between 0 and 100
+fn normalize(value: usize, cap: usize) -> f64 {
+    if cap == 0 {
+        return 0.0;
+    }
+
+    ((value as f64 / cap as f64) * 100.0).min(100.0)
+}
+
+/// The Score of each Category, between 0 and 100
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NormalizedScores {
+    pub war: f64,
+    pub cwl: f64,
+    pub raid: f64,
+    pub games: f64,
+}
+
+impl NormalizedScores {
+    pub fn total(&self) -> f64 {
+        self.war + self.cwl + self.raid + self.games
+    }
+
+    /// The Scores of every Category rounded for presentation
+    pub fn rounded(&self, rounding: Rounding) -> Self {
+        Self {
+            war: rounding.apply(self.war),
+            cwl: rounding.apply(self.cwl),
+            raid: rounding.apply(self.raid),
+            games: rounding.apply(self.games),
+        }
+    }
+}
+
+impl PlayerSummary {
+    /// The total Score using the default [`ScoreConfig`]
+    pub fn total_score(&self) -> f64 {
+        self.total_score_with(&ScoreConfig::default())
+    }
+
+    /// The total Score, where every category awards up to 100 Points
+    pub fn total_score_with(&self, cfg: &ScoreConfig) -> f64 {
+        self.normalized_with(cfg).total()
+    }
+
+    /// Whether the total Score reaches the threshold needed to qualify for the Gold-Pass
+    pub fn qualifies(&self, cfg: &ScoreConfig, threshold: f64) -> bool {
+        self.total_score_with(cfg) >= threshold
+    }
+
+    /// The Score of every Category on its own
+    pub fn normalized_with(&self, cfg: &ScoreConfig) -> NormalizedScores {
+        NormalizedScores {
+            war: normalize(self.war_stars, cfg.war_star_cap),
+            cwl: normalize(
+                self.cwl_stars,
+                cfg.cwl_star_cap.unwrap_or(DEFAULT_CWL_STAR_CAP),
+            ),
+            raid: normalize(self.raid_loot, cfg.capital_gold_cap),
+            games: normalize(self.games_score, cfg.games_cap),
+        }
+    }
+}
+
+impl Serialize for PlayerSummary {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let cfg = ScoreConfig::default();
+        let normalized = self.normalized_with(&cfg);
+
+        let mut state = serializer.serialize_struct("PlayerSummary", 6)?;
+        state.serialize_field("cwl_stars", &self.cwl_stars)?;
+        state.serialize_field("war_stars", &self.war_stars)?;
+        state.serialize_field("raid_loot", &self.raid_loot)?;
+        state.serialize_field("games_score", &self.games_score)?;
+        state.serialize_field("total", &cfg.rounding.apply(normalized.total()))?;
+        state.serialize_field("normalized", &normalized.rounded(cfg.rounding))?;
+        state.end()
+    }
+}
+
+impl ClanStorage {
+    /// The most CWL Stars a Player could have earned, based on the Rounds actually played
+    pub fn cwl_stars_max(&self) -> usize {
+        self.cwl.wars.len() * CWL_MAX_STARS_PER_ROUND
+    }
+
+    /// Fills in the parts of the Configuration that depend on the Stats of the Clan
+    pub fn score_config(&self, cfg: &ScoreConfig) -> ScoreConfig {
+        let mut cfg = cfg.clone();
+        if cfg.cwl_star_cap.is_none() && !self.cwl.wars.is_empty() {
+            cfg.cwl_star_cap = Some(self.cwl_stars_max());
+        }
+        cfg
+    }
+
+    /// The Summaries of all the included Players, with their Raid Loot weighted by the
+    /// District multipliers of the Configuration
+    pub fn weighted_summaries<'s>(
+        &'s self,
+        cfg: &'s ScoreConfig,
+    ) -> impl Iterator<Item = (PlayerTag, PlayerSummary)> + 's {
+        self.players_summary()
+            .filter(|(tag, _)| cfg.includes(tag))
+            .map(|(tag, mut summary)| {
+                if !cfg.district_multipliers.is_empty() {
+                    let loot: f64 = self
+                        .raid_weekend
+                        .values()
+                        .filter_map(|raid| raid.members.get(&tag))
+                        .map(|member| cfg.weighted_loot(member))
+                        .sum();
+                    summary.raid_loot = loot.round() as usize;
+                }
+
+                (tag, summary)
+            })
+    }
+
+    /// The Number of included Players that scored any Points at all
+    pub fn active_player_count(&self, cfg: &ScoreConfig) -> usize {
+        let cfg = &self.score_config(cfg);
+        self.weighted_summaries(cfg)
+            .filter(|(_, summary)| summary.total_score_with(cfg) > 0.0)
+            .count()
+    }
+
+    /// The mean total Score over all the included Players, 0 if there are none
+    pub fn average_score(&self, cfg: &ScoreConfig) -> f64 {
+        let cfg = &self.score_config(cfg);
+        let scores: Vec<f64> = self
+            .weighted_summaries(cfg)
+            .map(|(_, summary)| summary.total_score_with(cfg))
+            .collect();
+
+        if scores.is_empty() {
+            return 0.0;
+        }
+
+        scores.iter().sum::<f64>() / scores.len() as f64
+    }
+
+    /// Ranks the included Players by their total Score under the given Configuration, the
+    /// best Player first and ties ordered by Tag
+    pub fn preview_ranking(&self, cfg: &ScoreConfig) -> Vec<(PlayerTag, f64)> {
+        self.leaderboard(cfg)
+            .into_iter()
+            .map(|(tag, _, score)| (tag, score))
+            .collect()
+    }
+
+    /// The Summaries of all included Players together with their total Score, ordered by the
+    /// Score and then by the Tag so ties are always in the same order
+    pub fn leaderboard(&self, cfg: &ScoreConfig) -> Vec<(PlayerTag, PlayerSummary, f64)> {
+        let cfg = &self.score_config(cfg);
+        let mut leaderboard: Vec<(PlayerTag, PlayerSummary, f64)> = self
+            .weighted_summaries(cfg)
+            .map(|(tag, summary)| {
+                let score = summary.total_score_with(cfg);
+                (tag, summary, score)
+            })
+            .collect();
+
+        leaderboard.sort_unstable_by(|(t1, _, s1), (t2, _, s2)| {
+            s2.total_cmp(s1).then_with(|| t1.0.cmp(&t2.0))
+        });
+
+        leaderboard
+    }
+
+    /// The percentage of included Players whose total Score is at most the Score of the given
+    /// Player, so tied Players share the same percentile and the top Player is at 100
+    pub fn percentile(&self, player: &PlayerTag, cfg: &ScoreConfig) -> Option<f64> {
+        if !cfg.includes(player) {
+            return None;
+        }
+        let cfg = &self.score_config(cfg);
+
+        let scores: Vec<(PlayerTag, f64)> = self
+            .weighted_summaries(cfg)
+            .map(|(tag, summary)| (tag, summary.total_score_with(cfg)))
+            .collect();
+
+        let (_, player_score) = scores.iter().find(|(tag, _)| tag == player)?;
+        let below_or_equal = scores
+            .iter()
+            .filter(|(_, score)| score <= player_score)
+            .count();
+
+        Some(below_or_equal as f64 / scores.len() as f64 * 100.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        ClanTag, MemberWarStats, PlayerGamesStats, RaidAttack, RaidWeekendStats, Time, WarAttack,
+        WarTag,
+    };
+
+    fn with_games(scores: &[(&str, usize)]) -> ClanStorage {
+        let mut clan = ClanStorage::default();
+        for (tag, score) in scores {
+            let tag = PlayerTag(tag.to_string());
+            clan.player_names.insert(tag.clone(), tag.0.clone());
+            clan.games.insert(
+                tag,
+                PlayerGamesStats {
+                    start_score: Some(0),
+                    end_score: *score,
+                },
+            );
+        }
+        clan
+    }
+
+    #[test]
+    fn serialize_summary() {
+        let summary = PlayerSummary {
+            cwl_stars: 21,
+            war_stars: 33,
+            raid_loot: 30000,
+            games_score: 0,
+        };
+
+        let serialized = serde_json::to_value(&summary).unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "cwl_stars": 21,
+                "war_stars": 33,
+                "raid_loot": 30000,
+                "games_score": 0,
+                "total": 175.0,
+                "normalized": {
+                    "war": 50.0,
+                    "cwl": 100.0,
+                    "raid": 25.0,
+                    "games": 0.0,
+                },
+            }),
+            serialized
+        );
+
+        assert_eq!(
+            summary,
+            serde_json::from_value::<PlayerSummary>(serialized).unwrap()
+        );
+    }
+
+    #[test]
+    fn average_score() {
+        let clan = with_games(&[("#2PP", 5000), ("#2QQ", 2500), ("#2RR", 0)]);
+
+        assert_eq!(50.0, clan.average_score(&ScoreConfig::default()));
+
+        let mut cfg = ScoreConfig::default();
+        cfg.excluded.insert(PlayerTag("#2RR".to_string()));
+        assert_eq!(75.0, clan.average_score(&cfg));
+
+        assert_eq!(
+            0.0,
+            ClanStorage::default().average_score(&ScoreConfig::default())
+        );
+    }
+
+    #[test]
+    fn preview_ranking() {
+        let war_tag = PlayerTag("#2PP".to_string());
+        let games_tag = PlayerTag("#2QQ".to_string());
+
+        let mut clan = with_games(&[("#2PP", 0), ("#2QQ", 4000)]);
+        clan.raid_weekend.insert(
+            Time {
+                year: 2023,
+                month: 8,
+                day: 4,
+            },
+            RaidWeekendStats {
+                start_time: Time {
+                    year: 2023,
+                    month: 8,
+                    day: 4,
+                },
+                members: [(
+                    war_tag.clone(),
+                    RaidMember {
+                        looted: 60000,
+                        attacks_used: 0,
+                        attacks_available: 0,
+                        attacks: Vec::new(),
+                    },
+                )]
+                .into_iter()
+                .collect(),
+            },
+        );
+
+        let games_heavy = ScoreConfig::default();
+        let raid_heavy = ScoreConfig {
+            capital_gold_cap: 10000,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            vec![(games_tag.clone(), 80.0), (war_tag.clone(), 50.0)],
+            clan.preview_ranking(&games_heavy)
+        );
+        assert_eq!(
+            vec![(war_tag, 100.0), (games_tag, 80.0)],
+            clan.preview_ranking(&raid_heavy)
+        );
+    }
+
+    #[test]
+    fn district_multipliers() {
+        let weekend = |district: &str| RaidWeekendStats {
+            start_time: Time {
+                year: 2023,
+                month: 8,
+                day: 4,
+            },
+            members: [(
+                PlayerTag("#2PP".to_string()),
+                RaidMember {
+                    looted: 60000,
+                    attacks_used: 2,
+                    attacks_available: 6,
+                    attacks: vec![
+                        RaidAttack {
+                            defender: ClanTag("#9LL".to_string()),
+                            district: district.to_string(),
+                            district_hall_level: 5,
+                            destruction: 100,
+                            stars: 3,
+                        },
+                        RaidAttack {
+                            defender: ClanTag("#9LL".to_string()),
+                            district: "Barbarian Camp".to_string(),
+                            district_hall_level: 4,
+                            destruction: 100,
+                            stars: 3,
+                        },
+                    ],
+                },
+            )]
+            .into_iter()
+            .collect(),
+        };
+        let score = |clan: &ClanStorage, cfg: &ScoreConfig| {
+            let (_, summary) = clan.weighted_summaries(cfg).next().unwrap();
+            summary.normalized_with(cfg).raid
+        };
+
+        let mut clan = ClanStorage::default();
+        clan.player_names
+            .insert(PlayerTag("#2PP".to_string()), "Player".to_string());
+        clan.ingest_raid(weekend("Capital Peak")).unwrap();
+
+        assert_eq!(50.0, score(&clan, &ScoreConfig::default()));
+
+        let cfg = ScoreConfig {
+            district_multipliers: [("Capital Peak".to_string(), 2.0)].into_iter().collect(),
+            ..Default::default()
+        };
+        assert_eq!(75.0, score(&clan, &cfg));
+        assert_eq!(75.0, clan.average_score(&cfg));
+    }
+
+    #[test]
+    fn rounding() {
+        let score = 200.0 / 3.0;
+
+        assert_eq!(66.0, Rounding::Floor.apply(score));
+        assert_eq!(67.0, Rounding::Nearest.apply(score));
+        assert_eq!(66.67, Rounding::default().apply(score));
+        assert_eq!(66.7, Rounding::Decimals(1).apply(score));
+    }
+
+    #[test]
+    fn serialize_rounded_summary() {
+        let summary = PlayerSummary {
+            cwl_stars: 0,
+            war_stars: 44,
+            raid_loot: 0,
+            games_score: 0,
+        };
+        assert!((summary.total_score() - 200.0 / 3.0).abs() < 1e-9);
+
+        let value = serde_json::to_value(&summary).unwrap();
+        assert_eq!(serde_json::json!(66.67), value["total"]);
+        assert_eq!(serde_json::json!(66.67), value["normalized"]["war"]);
+    }
+
+    #[test]
+    fn active_player_count() {
+        let mut clan = with_games(&[("#2PP", 100), ("#2QQ", 0), ("#2RR", 2500), ("#2YY", 0)]);
+        clan.player_names
+            .insert(PlayerTag("#8YY".to_string()), "Inactive".to_string());
+
+        assert_eq!(5, clan.players_summary().count());
+        assert_eq!(2, clan.active_player_count(&ScoreConfig::default()));
+
+        let cfg = ScoreConfig {
+            excluded: [PlayerTag("#2RR".to_string())].into_iter().collect(),
+            ..Default::default()
+        };
+        assert_eq!(1, clan.active_player_count(&cfg));
+    }
+
+    #[test]
+    fn only_allowlisted() {
+        let clan = with_games(&[("#2PP", 100), ("#2QQ", 200), ("#2RR", 2500)]);
+
+        let cfg = ScoreConfig {
+            only: Some(
+                [PlayerTag("#2PP".to_string()), PlayerTag("#2RR".to_string())]
+                    .into_iter()
+                    .collect(),
+            ),
+            ..Default::default()
+        };
+        let ranked: Vec<PlayerTag> = clan
+            .preview_ranking(&cfg)
+            .into_iter()
+            .map(|(tag, _)| tag)
+            .collect();
+        assert_eq!(
+            vec![PlayerTag("#2RR".to_string()), PlayerTag("#2PP".to_string())],
+            ranked
+        );
+        assert_eq!(None, clan.percentile(&PlayerTag("#2QQ".to_string()), &cfg));
+
+        let cfg = ScoreConfig {
+            excluded: [PlayerTag("#2RR".to_string())].into_iter().collect(),
+            ..cfg
+        };
+        assert_eq!(1, clan.preview_ranking(&cfg).len());
+    }
+
+    #[test]
+    fn total_score() {
+        let perfect = PlayerSummary {
+            cwl_stars: 21,
+            war_stars: 80,
+            raid_loot: 120000,
+            games_score: 10000,
+        };
+        assert_eq!(400.0, perfect.total_score());
+
+        let half = PlayerSummary {
+            cwl_stars: 0,
+            war_stars: 33,
+            raid_loot: 60000,
+            games_score: 5000,
+        };
+        assert_eq!(200.0, half.total_score());
+    }
+
+    #[test]
+    fn custom_cwl_star_cap() {
+        let summary = PlayerSummary {
+            cwl_stars: 21,
+            war_stars: 0,
+            raid_loot: 0,
+            games_score: 0,
+        };
+        assert_eq!(100.0, summary.normalized_with(&ScoreConfig::default()).cwl);
+
+        let cfg = ScoreConfig {
+            cwl_star_cap: Some(35),
+            ..Default::default()
+        };
+        assert_eq!(60.0, summary.normalized_with(&cfg).cwl);
+        assert_eq!(60.0, summary.total_score_with(&cfg));
+    }
+
+    #[test]
+    fn leaderboard() {
+        let clan = with_games(&[("#2QQ", 1000), ("#2RR", 2500), ("#2PP", 1000)]);
+
+        let leaderboard: Vec<(String, usize, f64)> = clan
+            .leaderboard(&ScoreConfig::default())
+            .into_iter()
+            .map(|(tag, summary, score)| (tag.0, summary.games_score, score))
+            .collect();
+        assert_eq!(
+            vec![
+                ("#2RR".to_string(), 2500, 50.0),
+                ("#2PP".to_string(), 1000, 20.0),
+                ("#2QQ".to_string(), 1000, 20.0),
+            ],
+            leaderboard
+        );
+    }
+
+    #[test]
+    fn qualifies() {
+        let cfg = ScoreConfig::default();
+        let summary = |war_stars| PlayerSummary {
+            cwl_stars: 0,
+            war_stars,
+            raid_loot: 0,
+            games_score: 0,
+        };
+
+        assert!(summary(66).qualifies(&cfg, 100.0));
+        assert!(summary(33).qualifies(&cfg, 50.0));
+        assert!(!summary(32).qualifies(&cfg, 50.0));
+    }
+
+    #[test]
+    fn cwl_stars_max() {
+        let player = PlayerTag("#2PP".to_string());
+
+        let mut clan = ClanStorage::default();
+        clan.player_names
+            .insert(player.clone(), "Player".to_string());
+        for round in 0..5 {
+            let attack = WarAttack {
+                destruction: 100,
+                stars: 3,
+                duration: 100,
+                target: None,
+                order: round,
+                attacker_townhall: None,
+                defender_townhall: None,
+            };
+            clan.ingest_cwl_round(
+                WarTag(format!("#{}", round)),
+                round as u8,
+                [(
+                    player.clone(),
+                    MemberWarStats {
+                        attacks: vec![attack],
+                    },
+                )]
+                .into_iter()
+                .collect(),
+            )
+            .unwrap();
+        }
+
+        assert_eq!(15, clan.cwl_stars_max());
+        assert_eq!(
+            vec![(player, 100.0)],
+            clan.preview_ranking(&ScoreConfig::default())
+        );
+
+        let summary = PlayerSummary {
+            cwl_stars: 15,
+            war_stars: 0,
+            raid_loot: 0,
+            games_score: 0,
+        };
+        assert_eq!(
+            100.0,
+            summary
+                .normalized_with(&clan.score_config(&ScoreConfig::default()))
+                .cwl
+        );
+    }
+
+    #[test]
+    fn percentile() {
+        let clan = with_games(&[("#2PP", 5000), ("#2QQ", 2500), ("#2RR", 2500), ("#2YY", 0)]);
+        let cfg = ScoreConfig::default();
+
+        assert_eq!(
+            Some(100.0),
+            clan.percentile(&PlayerTag("#2PP".to_string()), &cfg)
+        );
+        assert_eq!(
+            Some(75.0),
+            clan.percentile(&PlayerTag("#2QQ".to_string()), &cfg)
+        );
+        assert_eq!(
+            Some(75.0),
+            clan.percentile(&PlayerTag("#2RR".to_string()), &cfg)
+        );
+        assert_eq!(
+            Some(25.0),
+            clan.percentile(&PlayerTag("#2YY".to_string()), &cfg)
+        );
+        assert_eq!(None, clan.percentile(&PlayerTag("#9LL".to_string()), &cfg));
+    }
+}
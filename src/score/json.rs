@@ -0,0 +1,151 @@
+use std::path::Path;
+
+use serde::Deserialize;
+
+use super::{normalize, Scorer};
+use crate::PlayerSummary;
+
+/// The Scoring of a single Category
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WeightedCategory {
+    /// The Value needed for the full 100 Points of the Category
+    pub denominator: usize,
+    /// The factor applied to the normalized Points of the Category
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+}
+
+type SummaryValue = fn(&PlayerSummary) -> usize;
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+/// A [`Scorer`] loaded from a JSON Config, only the Categories present in the Config are
+/// considered for the Score
+///
+/// ```json
+/// {
+///     "war": { "denominator": 66 },
+///     "raid": { "denominator": 120000, "weight": 0.5 }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JsonScorer {
+    #[serde(default)]
+    pub war: Option<WeightedCategory>,
+    #[serde(default)]
+    pub cwl: Option<WeightedCategory>,
+    #[serde(default)]
+    pub raid: Option<WeightedCategory>,
+    #[serde(default)]
+    pub games: Option<WeightedCategory>,
+}
+
+#[derive(Debug)]
+pub enum JsonScorerError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+    /// The Category has a denominator of 0
+    ZeroDenominator(&'static str),
+}
+
+impl std::fmt::Display for JsonScorerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Reading Scorer Config: {}", e),
+            Self::Parse(e) => write!(f, "Parsing Scorer Config: {}", e),
+            Self::ZeroDenominator(category) => {
+                write!(f, "Category {:?} has a denominator of 0", category)
+            }
+        }
+    }
+}
+
+impl std::error::Error for JsonScorerError {}
+
+impl JsonScorer {
+    /// Parses and validates the Config
+    pub fn from_json(content: &str) -> Result<Self, JsonScorerError> {
+        let scorer: Self = serde_json::from_str(content).map_err(JsonScorerError::Parse)?;
+
+        for (name, category, _) in scorer.categories() {
+            if category.denominator == 0 {
+                return Err(JsonScorerError::ZeroDenominator(name));
+            }
+        }
+
+        Ok(scorer)
+    }
+
+    /// Loads and validates the Config from the File
+    pub fn from_file<P>(path: P) -> Result<Self, JsonScorerError>
+    where
+        P: AsRef<Path>,
+    {
+        let content = std::fs::read_to_string(path).map_err(JsonScorerError::Io)?;
+        Self::from_json(&content)
+    }
+
+    /// The configured Categories, with their Name and the Value of a Summary they score
+    fn categories(&self) -> impl Iterator<Item = (&'static str, &WeightedCategory, SummaryValue)> {
+        let categories: [(_, _, SummaryValue); 4] = [
+            ("war", self.war.as_ref(), |s| s.war_stars),
+            ("cwl", self.cwl.as_ref(), |s| s.cwl_stars),
+            ("raid", self.raid.as_ref(), |s| s.raid_loot),
+            ("games", self.games.as_ref(), |s| s.games_score),
+        ];
+
+        categories
+            .into_iter()
+            .filter_map(|(name, category, value)| category.map(|c| (name, c, value)))
+    }
+}
+
+impl Scorer for JsonScorer {
+    fn score(&self, summary: &PlayerSummary) -> f64 {
+        self.categories()
+            .map(|(_, category, value)| {
+                normalize(value(summary), category.denominator) * category.weight
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_and_score() {
+        let path = std::env::temp_dir().join(format!("scorer-{}.json", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"{
+                "war": { "denominator": 50 },
+                "raid": { "denominator": 100000, "weight": 0.5 }
+            }"#,
+        )
+        .unwrap();
+
+        let scorer = JsonScorer::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let summary = PlayerSummary {
+            cwl_stars: 21,
+            war_stars: 25,
+            raid_loot: 100000,
+            games_score: 5000,
+        };
+        assert_eq!(100.0, scorer.score(&summary));
+    }
+
+    #[test]
+    fn zero_denominator() {
+        assert!(matches!(
+            JsonScorer::from_json(r#"{ "games": { "denominator": 0 } }"#),
+            Err(JsonScorerError::ZeroDenominator("games"))
+        ));
+    }
+}
@@ -2,12 +2,25 @@ use std::{
     collections::{BTreeMap, HashMap, HashSet},
     future::Future,
     pin::Pin,
+    sync::OnceLock,
 };
 
 use chrono::Datelike;
 use serde::{Deserialize, Serialize};
 
-use crate::{ClanTag, ClanWarLeagueSeason, PlayerTag, Time};
+use crate::{ClanTag, ClanWarLeagueSeason, Clock, PlayerTag, SystemClock, Time, WarTag};
+
+mod gzip;
+pub use gzip::GzipStorage;
+
+mod encrypted;
+pub use encrypted::EncryptedStorage;
+
+mod heal;
+pub use heal::HealFix;
+
+mod migrations;
+pub use migrations::{Migration, MigrationError, Migrations};
 
 mod files;
 pub use files::FileStorage;
@@ -18,12 +31,113 @@ pub use s3::S3Storage;
 mod replicated;
 pub use replicated::Replicated;
 
+mod tee;
+pub use tee::TeeStorage;
+
+mod retry;
+pub use retry::RetryStorage;
+
+mod perclan;
+pub use perclan::{merge_streaming, ClanBackend, ClanDirectory, ClanSeasons};
+
+mod memory;
+pub use memory::MemoryStorage;
+
+mod shared;
+pub use shared::{ClanGuard, SharedStorage};
+
+#[derive(Debug)]
+pub enum StorageError {
+    /// The Storage could not be (de-)serialized
+    Serialization(serde_json::Error),
+    /// The Storage could not be (de-)compressed
+    Compression(std::io::Error),
+    /// The Storage could not be en- or decrypted
+    Encryption(&'static str),
+    /// Reading or Writing the underlying File failed
+    Io(std::io::Error),
+    /// The Backend itself reported an Error
+    Backend(String),
+    /// There is no stored Content
+    NotFound,
+    /// The stored Content was changed by someone else since it was loaded, so it should be
+    /// loaded again before retrying
+    Conflict,
+}
+
+impl std::fmt::Display for StorageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Serialization(e) => write!(f, "Serialization: {}", e),
+            Self::Compression(e) => write!(f, "Compression: {}", e),
+            Self::Encryption(e) => write!(f, "Encryption: {}", e),
+            Self::Io(e) => write!(f, "IO: {}", e),
+            Self::Backend(e) => write!(f, "Backend: {}", e),
+            Self::NotFound => write!(f, "Not Found"),
+            Self::Conflict => write!(f, "Conflicting Write"),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Serialization(e) => Some(e),
+            Self::Compression(e) => Some(e),
+            Self::Io(e) => Some(e),
+            Self::Encryption(_) | Self::Backend(_) | Self::NotFound | Self::Conflict => None,
+        }
+    }
+}
+
+impl StorageError {
+    /// Whether the Operation could succeed when simply retrying it, like for a failed Network
+    /// Request, as opposed to Errors that would fail the same way every time
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Io(_) | Self::Backend(_))
+    }
+}
+
+impl From<serde_json::Error> for StorageError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Serialization(value)
+    }
+}
+
+/// The Error returned by [`Storage::apply`]
+#[derive(Debug)]
+pub enum ApplyError<E> {
+    /// The stored Storage could not be loaded, so nothing was updated to avoid overwriting it
+    Load(StorageError),
+    /// The Update itself failed, so nothing was saved
+    Update(E),
+    /// The updated Storage could not be saved
+    Save(StorageError),
+}
+
 pub trait StorageBackend: Send {
     fn write(
         &mut self,
         content: Vec<u8>,
-    ) -> Pin<Box<dyn Future<Output = Result<(), ()>> + Send + 'static>>;
-    fn load(&mut self) -> Pin<Box<dyn Future<Output = Result<Vec<u8>, ()>> + Send + 'static>>;
+    ) -> Pin<Box<dyn Future<Output = Result<(), StorageError>> + Send + 'static>>;
+    fn load(
+        &mut self,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<u8>, StorageError>> + Send + 'static>>;
+
+    /// Whether there is any stored Content, by default this loads the Content so Backends should
+    /// override it with a cheaper check if possible
+    fn exists(
+        &mut self,
+    ) -> Pin<Box<dyn Future<Output = Result<bool, StorageError>> + Send + 'static>> {
+        let load = self.load();
+        Box::pin(async move {
+            match load.await {
+                Ok(_) => Ok(true),
+                Err(StorageError::NotFound) => Ok(false),
+                Err(e) => Err(e),
+            }
+        })
+    }
 }
 
 impl<S> StorageBackend for Box<S>
@@ -33,21 +147,48 @@ where
     fn write(
         &mut self,
         content: Vec<u8>,
-    ) -> Pin<Box<dyn Future<Output = Result<(), ()>> + Send + 'static>> {
+    ) -> Pin<Box<dyn Future<Output = Result<(), StorageError>> + Send + 'static>> {
         S::write(self.as_mut(), content)
     }
 
-    fn load(&mut self) -> Pin<Box<dyn Future<Output = Result<Vec<u8>, ()>> + Send + 'static>> {
+    fn load(
+        &mut self,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<u8>, StorageError>> + Send + 'static>> {
         S::load(self.as_mut())
     }
+
+    fn exists(
+        &mut self,
+    ) -> Pin<Box<dyn Future<Output = Result<bool, StorageError>> + Send + 'static>> {
+        S::exists(self.as_mut())
+    }
 }
 
+/// The Version of the serialized [`Storage`] written by this Build
+pub const STORAGE_VERSION: u32 = 1;
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Storage {
+    /// The Version of the stored Format, files written before this was tracked are Version 0
+    #[serde(default)]
+    version: u32,
+    /// Increased every time the Storage is saved with changed Stats, so the newest of multiple
+    /// stored Copies can be identified
+    #[serde(default)]
+    revision: u64,
     clans: HashMap<ClanTag, HashMap<Season, ClanStorage>>,
+    /// The Checksum of the Stats when they were last loaded or saved, so saving unchanged Stats
+    /// keeps the Revision and writes the exact same Bytes again
+    #[serde(skip)]
+    saved_checksum: Option<u64>,
+    /// Lazily built reverse lookup from a Season and Player to the Clans they are tracked in,
+    /// reset whenever the Storage is mutated
+    #[serde(skip)]
+    player_index: OnceLock<HashMap<Season, HashMap<PlayerTag, Vec<ClanTag>>>>,
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+/// Seasons are ordered chronologically, first by the Year and then by the Month
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 pub struct Season {
     pub year: usize,
     pub month: usize,
@@ -58,14 +199,22 @@ impl<'de> Deserialize<'de> for Season {
     where
         D: serde::Deserializer<'de>,
     {
-        let raw = String::deserialize(deserializer)?;
-
-        let (raw_year, raw_month) = raw.split_once('-').ok_or(serde::de::Error::custom(""))?;
+        /// Older configs specified the Season as a map instead of the `YYYY-MM` String
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum RawSeason {
+            Str(String),
+            Map { year: usize, month: usize },
+        }
 
-        let year = raw_year.parse().map_err(|e| serde::de::Error::custom(e))?;
-        let month = raw_month.parse().map_err(|e| serde::de::Error::custom(e))?;
+        let raw = match RawSeason::deserialize(deserializer)? {
+            RawSeason::Str(raw) => raw,
+            RawSeason::Map { year, month } => {
+                return Self::from_ymd(year, month).map_err(serde::de::Error::custom)
+            }
+        };
 
-        Ok(Self { year, month })
+        raw.parse().map_err(serde::de::Error::custom)
     }
 }
 impl Serialize for Season {
@@ -73,8 +222,31 @@ impl Serialize for Season {
     where
         S: serde::Serializer,
     {
-        let raw = format!("{:04}-{:02}", self.year, self.month);
-        raw.serialize(serializer)
+        self.to_string().serialize(serializer)
+    }
+}
+
+impl std::fmt::Display for Season {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}", self.year, self.month)
+    }
+}
+
+impl std::str::FromStr for Season {
+    type Err = SeasonError;
+
+    /// Parses a Season in the form `YYYY-MM`
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let (raw_year, raw_month) = raw.split_once('-').ok_or(SeasonError::MissingSeparator)?;
+
+        let year = raw_year
+            .parse()
+            .map_err(|_| SeasonError::InvalidNumber(raw_year.to_string()))?;
+        let month = raw_month
+            .parse()
+            .map_err(|_| SeasonError::InvalidNumber(raw_month.to_string()))?;
+
+        Self::from_ymd(year, month)
     }
 }
 
@@ -95,23 +267,111 @@ impl From<ClanWarLeagueSeason> for Season {
     }
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub enum SeasonError {
+    /// The Month is not in the range 1 to 12
+    InvalidMonth(usize),
+    /// The Season is not in the form `YYYY-MM`
+    MissingSeparator,
+    /// The Year or Month is not a Number
+    InvalidNumber(String),
+}
+
+impl std::fmt::Display for SeasonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidMonth(month) => write!(f, "Invalid Month {}", month),
+            Self::MissingSeparator => write!(f, "Expected a Season in the form YYYY-MM"),
+            Self::InvalidNumber(raw) => write!(f, "Invalid Number {:?}", raw),
+        }
+    }
+}
+
+impl std::error::Error for SeasonError {}
+
 impl Season {
+    /// Creates the Season for the given Month, which has to be in the range 1 to 12
+    pub fn from_ymd(year: usize, month: usize) -> Result<Self, SeasonError> {
+        if !(1..=12).contains(&month) {
+            return Err(SeasonError::InvalidMonth(month));
+        }
+
+        Ok(Self { year, month })
+    }
+
     pub fn current() -> Self {
-        let now = chrono::Utc::now();
+        Self::current_with(&SystemClock)
+    }
+
+    /// The Season the Clock is currently in
+    pub fn current_with(clock: &dyn Clock) -> Self {
+        let now = clock.now();
         Self {
             year: now.year() as usize,
             month: now.month() as usize,
         }
     }
 
+    /// Whether the Season is still ongoing, in which case its Stats are not final yet
+    pub fn is_provisional(&self, clock: &dyn Clock) -> bool {
+        *self == Self::current_with(clock)
+    }
+
     pub fn previous(&self) -> Self {
-        let mut year = self.year;
-        let mut month = self.month - 1;
-        if month < 1 {
-            year -= 1;
-            month = 12;
+        if self.month <= 1 {
+            return Self {
+                year: self.year.saturating_sub(1),
+                month: 12,
+            };
+        }
+
+        Self {
+            year: self.year,
+            month: self.month - 1,
+        }
+    }
+
+    /// Every Season from `from` up to and including `to`, nothing if `from` is after `to`
+    pub fn range(from: Season, to: Season) -> impl Iterator<Item = Season> {
+        let mut current = (from <= to).then_some(from);
+        std::iter::from_fn(move || {
+            let season = current.take()?;
+            // Only moving on if the next Season is actually later ensures this always ends, even
+            // for Seasons with an out of range Month
+            let next = season.next();
+            if next > season && next <= to {
+                current = Some(next);
+            }
+            Some(season)
+        })
+    }
+
+    pub fn next(&self) -> Self {
+        if self.month >= 12 {
+            return Self {
+                year: self.year.saturating_add(1),
+                month: 1,
+            };
+        }
+
+        Self {
+            year: self.year,
+            month: self.month + 1,
         }
-        Self { year, month }
+    }
+}
+
+/// An inclusive Range of Seasons, as used in Report Configs
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SeasonRange {
+    pub from: Season,
+    pub to: Season,
+}
+
+impl SeasonRange {
+    /// All the Seasons in the Range, empty if `from` is after `to`
+    pub fn seasons(&self) -> Vec<Season> {
+        Season::range(self.from.clone(), self.to.clone()).collect()
     }
 }
 
@@ -125,58 +385,441 @@ pub struct ClanStorage {
     pub games: HashMap<PlayerTag, PlayerGamesStats>,
     pub raid_weekend: BTreeMap<Time, RaidWeekendStats>,
     pub player_names: HashMap<PlayerTag, String>,
+    /// Whether the Season has been closed, in which case it will not accept any more data
+    #[serde(default)]
+    pub closed: bool,
+    /// When the Stats were last refreshed from the API
+    #[serde(default)]
+    pub last_updated: Option<Time>,
 }
 
-#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[derive(Debug, PartialEq, Eq)]
+pub enum IngestError {
+    /// The Season has been closed and can therefore not be modified anymore
+    SeasonClosed,
+    /// The Clan has not been registered in the Storage
+    UnknownClan,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
 pub struct PlayerGamesStats {
     pub start_score: Option<usize>,
     pub end_score: usize,
 }
 
+/// The most Stars a single Member can earn in one CWL Round
+pub const CWL_MAX_STARS_PER_ROUND: usize = 3;
+
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct CwlStats {
     pub wars: Vec<CwlWarStats>,
 }
 
-#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
 pub struct CwlWarStats {
+    /// The Tag of the War, missing for data recorded before this was tracked
+    #[serde(default)]
+    pub war_tag: Option<WarTag>,
+    /// The Round of the War in the League, starting at 0
+    #[serde(default)]
+    pub round: u8,
     pub members: HashMap<PlayerTag, MemberWarStats>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct WarStats {
     pub start_time: Time,
     pub members: HashMap<PlayerTag, MemberWarStats>,
+    /// The Number of Attacks every Member has in this War
+    #[serde(default = "default_attacks_per_member")]
+    pub attacks_per_member: usize,
+    /// The Clan we were matched against, missing for data recorded before this was tracked
+    #[serde(default)]
+    pub opponent: Option<ClanTag>,
+    /// The Result of the War, only known once the War has ended
+    #[serde(default)]
+    pub result: Option<WarResult>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum WarResult {
+    Win,
+    Loss,
+    /// Stored as `Draw` before it was renamed
+    #[serde(alias = "Draw")]
+    Tie,
+}
+
+/// A single War in the [`ClanStorage::war_history`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct WarHistoryRow {
+    pub time: Time,
+    pub opponent: Option<ClanTag>,
+    pub result: Option<WarResult>,
+    /// The Stars our Clan earned, only counting the best Attack on every Base
+    pub stars: usize,
+    /// The total Destruction our Clan achieved, between 0 and 100
+    pub destruction: f64,
+}
+
+fn default_attacks_per_member() -> usize {
+    2
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct MemberWarStats {
     pub attacks: Vec<WarAttack>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct WarAttack {
     pub destruction: usize,
     pub stars: usize,
     pub duration: usize,
+    /// The Base that was attacked, missing for data recorded before this was tracked
+    #[serde(default)]
+    pub target: Option<PlayerTag>,
+    /// The Order of the Attack in the entire War
+    #[serde(default)]
+    pub order: usize,
+    /// The Town Hall Level of the Attacker
+    #[serde(default)]
+    pub attacker_townhall: Option<u8>,
+    /// The Town Hall Level of the attacked Base
+    #[serde(default)]
+    pub defender_townhall: Option<u8>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+/// The aggregated Results of a Group of Attacks
+#[derive(Debug, Clone, PartialEq)]
+pub struct StarStats {
+    pub attacks: usize,
+    pub average_stars: f64,
+    pub average_destruction: f64,
+}
+
+/// The Members with fewer than the expected Number of Attacks, with the Number of Attacks they
+/// missed, sorted by their Tag
+fn missed_attacks(
+    members: &HashMap<PlayerTag, MemberWarStats>,
+    expected_attacks: usize,
+) -> Vec<(PlayerTag, usize)> {
+    let mut missed: Vec<(PlayerTag, usize)> = members
+        .iter()
+        .filter_map(|(tag, stats)| {
+            let missed = expected_attacks.saturating_sub(stats.attacks.len());
+            (missed > 0).then(|| (tag.clone(), missed))
+        })
+        .collect();
+    missed.sort_unstable_by(|(t1, _), (t2, _)| t1.0.cmp(&t2.0));
+
+    missed
+}
+
+/// Calculates the Stars each Member actually added to the War, as a later Attack on an already
+/// attacked Base only counts the Stars that improve on the previous best Attack
+fn net_new_stars(members: &HashMap<PlayerTag, MemberWarStats>) -> HashMap<PlayerTag, usize> {
+    let mut attacks: Vec<(&PlayerTag, &WarAttack)> = members
+        .iter()
+        .flat_map(|(tag, stats)| stats.attacks.iter().map(move |attack| (tag, attack)))
+        .collect();
+    attacks.sort_by_key(|(_, attack)| attack.order);
+
+    let mut best: HashMap<&PlayerTag, usize> = HashMap::new();
+    let mut result: HashMap<PlayerTag, usize> =
+        members.keys().map(|tag| (tag.clone(), 0)).collect();
+
+    for (attacker, attack) in attacks {
+        let new_stars = match attack.target.as_ref() {
+            Some(target) => {
+                let previous = best.entry(target).or_insert(0);
+                let new_stars = attack.stars.saturating_sub(*previous);
+                *previous = (*previous).max(attack.stars);
+                new_stars
+            }
+            None => attack.stars,
+        };
+
+        let stars = result
+            .get_mut(attacker)
+            .expect("Every attacker is a member");
+        *stars = stars.saturating_add(new_stars);
+    }
+
+    result
+}
+
+/// A stable 64-bit FNV-1a Hash of the Content
+fn fnv1a(content: &[u8]) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    content.iter().fold(FNV_OFFSET, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Sums up the Values, without overflowing for absurdly large Values in corrupted Data
+fn saturating_sum<I>(values: I) -> usize
+where
+    I: IntoIterator<Item = usize>,
+{
+    values.into_iter().fold(0, usize::saturating_add)
+}
+
+/// Merges the Clan Games Stats of the same Player, keeping the lowest start and highest end
+fn merge_games(target: &mut PlayerGamesStats, other: &PlayerGamesStats) {
+    target.start_score = match (target.start_score, other.start_score) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+    target.end_score = target.end_score.max(other.end_score);
+}
+
+/// Moves the Value stored for `from` to `to`, merging it into an already existing Value
+fn rename_key<V, F>(map: &mut HashMap<PlayerTag, V>, from: &PlayerTag, to: &PlayerTag, merge: F)
+where
+    F: FnOnce(&mut V, V),
+{
+    let value = match map.remove(from) {
+        Some(v) => v,
+        None => return,
+    };
+
+    match map.get_mut(to) {
+        Some(existing) => merge(existing, value),
+        None => {
+            map.insert(to.clone(), value);
+        }
+    }
+}
+
+/// Merges the Members of the same War, keeping whichever entry has more Attacks recorded
+fn merge_members(
+    target: &mut HashMap<PlayerTag, MemberWarStats>,
+    other: &HashMap<PlayerTag, MemberWarStats>,
+) {
+    for (tag, stats) in other {
+        match target.get_mut(tag) {
+            Some(existing) if existing.attacks.len() >= stats.attacks.len() => {}
+            Some(existing) => *existing = stats.clone(),
+            None => {
+                target.insert(tag.clone(), stats.clone());
+            }
+        }
+    }
+}
+
+impl MemberWarStats {
+    /// Orders the Attacks by their Order in the War
+    pub fn sort_attacks(&mut self) {
+        self.attacks.sort_by_key(|attack| attack.order);
+    }
+
+    /// The Stars of the Member, only counting the best Attack on every Base. Attacks without a
+    /// known Target are all counted
+    pub fn effective_stars(&self) -> usize {
+        let mut best: HashMap<&PlayerTag, usize> = HashMap::new();
+        let mut untargeted = 0usize;
+
+        for attack in self.attacks.iter() {
+            match attack.target.as_ref() {
+                Some(target) => {
+                    let entry = best.entry(target).or_insert(0);
+                    *entry = (*entry).max(attack.stars);
+                }
+                None => untargeted = untargeted.saturating_add(attack.stars),
+            }
+        }
+
+        saturating_sum(best.into_values()).saturating_add(untargeted)
+    }
+}
+
+impl WarStats {
+    /// The total Destruction of the War, where every Base counts with its best Attack and the
+    /// Opponent is assumed to have as many Bases as we have Members
+    pub fn destruction(&self) -> f64 {
+        if self.members.is_empty() {
+            return 0.0;
+        }
+
+        let mut best: HashMap<&PlayerTag, usize> = HashMap::new();
+        for attack in self.members.values().flat_map(|stats| stats.attacks.iter()) {
+            if let Some(target) = attack.target.as_ref() {
+                let entry = best.entry(target).or_insert(0);
+                *entry = (*entry).max(attack.destruction.min(100));
+            }
+        }
+
+        best.values().sum::<usize>() as f64 / self.members.len() as f64
+    }
+
+    /// The Members that did not use all their Attacks, with the Number of Attacks they missed,
+    /// sorted by their Tag
+    pub fn missed_attacks(&self) -> Vec<(PlayerTag, usize)> {
+        missed_attacks(&self.members, self.attacks_per_member)
+    }
+
+    /// The Members with fewer than the expected Number of Attacks, sorted by their Tag, like
+    /// [`WarStats::missed_attacks`] but for a custom Number of Attacks
+    pub fn missed_attackers(&self, expected_attacks: usize) -> Vec<PlayerTag> {
+        missed_attacks(&self.members, expected_attacks)
+            .into_iter()
+            .map(|(tag, _)| tag)
+            .collect()
+    }
+
+    /// The Stars each Member actually contributed to the War, only counting the Stars of an
+    /// Attack that improve on the best previous Attack on the same Base
+    pub fn net_new_stars(&self) -> HashMap<PlayerTag, usize> {
+        net_new_stars(&self.members)
+    }
+}
+
+impl CwlStats {
+    /// All the Wars ordered by their Round
+    pub fn sorted_wars(&self) -> Vec<&CwlWarStats> {
+        let mut wars: Vec<&CwlWarStats> = self.wars.iter().collect();
+        wars.sort_by_key(|war| war.round);
+        wars
+    }
+
+    /// The War with the given Tag, falling back to a War without a Tag at the Position of the
+    /// Round, as Wars recorded before the Tag was tracked were stored by their Round
+    fn war_mut(&mut self, war_tag: &WarTag, round: u8) -> Option<&mut CwlWarStats> {
+        let index = match self
+            .wars
+            .iter()
+            .position(|war| war.war_tag.as_ref() == Some(war_tag))
+        {
+            Some(index) => index,
+            None => {
+                let index = usize::from(round);
+                match self.wars.get(index) {
+                    Some(war) if war.war_tag.is_none() => index,
+                    _ => return None,
+                }
+            }
+        };
+
+        self.wars.get_mut(index)
+    }
+}
+
+impl CwlWarStats {
+    /// The Members that did not use their single Attack, sorted by their Tag
+    pub fn missed_attackers(&self) -> Vec<PlayerTag> {
+        missed_attacks(&self.members, 1)
+            .into_iter()
+            .map(|(tag, _)| tag)
+            .collect()
+    }
+
+    /// The Stars each Member actually contributed to the War, only counting the Stars of an
+    /// Attack that improve on the best previous Attack on the same Base
+    pub fn net_new_stars(&self) -> HashMap<PlayerTag, usize> {
+        net_new_stars(&self.members)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct RaidWeekendStats {
     pub start_time: Time,
     pub members: HashMap<PlayerTag, RaidMember>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+impl RaidWeekendStats {
+    /// The Fraction of the available Attacks that were actually used by all the Members, 0 if
+    /// no available Attacks are known
+    pub fn participation(&self) -> f64 {
+        let (used, available) =
+            self.members
+                .values()
+                .fold((0usize, 0usize), |(used, available), member| {
+                    (
+                        used.saturating_add(member.attacks_used.min(member.attacks_available)),
+                        available.saturating_add(member.attacks_available),
+                    )
+                });
+
+        if available == 0 {
+            return 0.0;
+        }
+
+        used as f64 / available as f64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct RaidMember {
     pub looted: usize,
+    /// The Number of Attacks used during the Weekend, 0 for data recorded before this was tracked
+    #[serde(default)]
+    pub attacks_used: usize,
+    /// The Number of Attacks the Member could have used, including Bonus Attacks, 0 for data
+    /// recorded before this was tracked
+    #[serde(default)]
+    pub attacks_available: usize,
+    /// The individual Attacks from the Raid Log, empty for data recorded before this was tracked
+    #[serde(default)]
+    pub attacks: Vec<RaidAttack>,
+}
+
+/// A single Attack on a District of another Clan's Capital
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RaidAttack {
+    /// The Clan whose Capital was attacked
+    pub defender: ClanTag,
+    /// The Name of the attacked District
+    pub district: String,
+    pub district_hall_level: usize,
+    pub destruction: usize,
+    pub stars: usize,
 }
 
 impl Storage {
     pub fn empty() -> Self {
         Self {
+            version: STORAGE_VERSION,
+            revision: 0,
             clans: HashMap::new(),
+            saved_checksum: None,
+            player_index: OnceLock::new(),
+        }
+    }
+
+    /// Creates a Storage with all the given Clans already registered
+    pub fn new_with_clans<I>(tags: I) -> Self
+    where
+        I: IntoIterator<Item = ClanTag>,
+    {
+        let mut storage = Self::empty();
+        for tag in tags {
+            storage.register_clan(tag);
+        }
+        storage
+    }
+
+    /// The Version of the stored Format, see [`STORAGE_VERSION`]
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Upgrades Data loaded from an older Version to the current [`STORAGE_VERSION`]
+    pub fn migrate(&mut self) {
+        if self.version >= STORAGE_VERSION {
+            return;
         }
+
+        tracing::info!(
+            "Migrating Storage from Version {} to {}",
+            self.version,
+            STORAGE_VERSION
+        );
+
+        // Version 0 only lacked the Version itself, so there is nothing else to upgrade yet
+        self.version = STORAGE_VERSION;
     }
 
     pub fn register_clan(&mut self, tag: ClanTag) {
@@ -184,10 +827,70 @@ impl Storage {
             return;
         }
 
+        self.player_index = OnceLock::new();
         self.clans.insert(tag, HashMap::new());
     }
 
+    /// Merges the other Storage into this one, like when combining a Backup with newer Data.
+    ///
+    /// Clans and Seasons only stored in one of them are kept as is. For Seasons stored in both,
+    /// the Stats are merged using [`ClanStorage::merge_from`], so for every War, CWL Round, Raid
+    /// and Player the entry with more data wins, known Names are kept and the newer
+    /// `last_updated` is used. The higher Revision of both is kept and the next Save always
+    /// increases it, so the merged Storage is newer than either of them
+    pub fn merge(&mut self, other: Storage) {
+        self.player_index = OnceLock::new();
+        self.revision = self.revision.max(other.revision);
+        self.saved_checksum = None;
+
+        for (tag, seasons) in other.clans {
+            let existing = self.clans.entry(tag).or_default();
+            for (season, stats) in seasons {
+                match existing.get_mut(&season) {
+                    Some(current) => current.merge_from(&stats),
+                    None => {
+                        existing.insert(season, stats);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Removes the Clan together with all its Seasons, returns whether it was registered
+    pub fn remove_clan(&mut self, tag: &ClanTag) -> bool {
+        self.player_index = OnceLock::new();
+        self.clans.remove(tag).is_some()
+    }
+
+    /// Removes a single Season of the Clan, the Clan itself stays registered even if this was
+    /// its last Season. Returns whether the Season was stored
+    pub fn remove_season(&mut self, tag: &ClanTag, season: &Season) -> bool {
+        self.player_index = OnceLock::new();
+        self.clans
+            .get_mut(tag)
+            .map(|seasons| seasons.remove(season).is_some())
+            .unwrap_or(false)
+    }
+
+    /// Removes every Season older than `keep_from` from all the Clans, returns the Number of
+    /// Seasons that were removed
+    pub fn prune_seasons_older_than(&mut self, keep_from: Season) -> usize {
+        self.player_index = OnceLock::new();
+
+        self.clans
+            .values_mut()
+            .map(|seasons| {
+                let before = seasons.len();
+                seasons.retain(|season, _| *season >= keep_from);
+                before - seasons.len()
+            })
+            .sum()
+    }
+
     pub fn get_mut(&mut self, tag: &ClanTag, season: &Season) -> Option<&mut ClanStorage> {
+        // The caller could change anything about the players, so the index has to be rebuilt
+        self.player_index = OnceLock::new();
+
         self.clans.get_mut(tag).map(|seasons| {
             if !seasons.contains_key(season) {
                 seasons.insert(season.clone(), ClanStorage::default());
@@ -197,92 +900,2647 @@ impl Storage {
         })
     }
 
-    pub fn get(&self, tag: &ClanTag, season: &Season) -> Option<&ClanStorage> {
-        self.clans.get(tag).and_then(|s| s.get(season))
+    /// Iterates over the Stats of every Clan and Season
+    pub fn iter(&self) -> impl Iterator<Item = (&ClanTag, &Season, &ClanStorage)> {
+        self.clans.iter().flat_map(|(tag, seasons)| {
+            seasons
+                .iter()
+                .map(move |(season, stats)| (tag, season, stats))
+        })
     }
 
-    pub async fn load(store: &mut dyn StorageBackend) -> Result<Self, ()> {
-        let content = store.load().await.map_err(|e| ())?;
-        serde_json::from_slice(&content).map_err(|e| ())
+    /// Mutably iterates over the Stats of every Clan and Season
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&ClanTag, &Season, &mut ClanStorage)> {
+        self.player_index = OnceLock::new();
+
+        self.clans.iter_mut().flat_map(|(tag, seasons)| {
+            seasons
+                .iter_mut()
+                .map(move |(season, stats)| (tag, season, stats))
+        })
     }
 
-    pub async fn save(&self, store: &mut dyn StorageBackend) -> Result<(), ()> {
-        let content = serde_json::to_vec(&self).map_err(|e| {
-            tracing::error!("Serializing {:?}", e);
-            ()
-        })?;
+    /// All the registered Clans
+    pub fn clans(&self) -> impl Iterator<Item = &ClanTag> {
+        self.clans.keys()
+    }
 
-        store.write(content).await.map_err(|e| {
-            tracing::error!("Storing {:?}", e);
-            ()
-        })
+    /// All the Seasons stored for the Clan, including the ones without any Stats
+    pub fn seasons(&self, tag: &ClanTag) -> Vec<Season> {
+        self.clans
+            .get(tag)
+            .map(|seasons| seasons.keys().cloned().collect())
+            .unwrap_or_default()
     }
-}
 
-#[derive(Debug)]
-pub struct PlayerSummary {
-    pub cwl_stars: usize,
-    pub war_stars: usize,
-    pub raid_loot: usize,
-    pub games_score: usize,
-}
+    /// All the registered Clans ordered by their Tag
+    pub fn list_clans(&self) -> Vec<ClanTag> {
+        let mut clans: Vec<ClanTag> = self.clans.keys().cloned().collect();
+        clans.sort_by(|c1, c2| c1.0.cmp(&c2.0));
+        clans
+    }
 
-impl ClanStorage {
-    pub fn players_summary(&self) -> impl Iterator<Item = (PlayerTag, PlayerSummary)> + '_ {
-        // TODO
-        // Get all the players we have some data for
-        let players: HashSet<PlayerTag> = self.player_names.keys().cloned().collect();
+    /// All the Seasons stored for the Clan ordered chronologically, see [`Storage::seasons`]
+    pub fn list_seasons(&self, tag: &ClanTag) -> Vec<Season> {
+        let mut seasons = self.seasons(tag);
+        seasons.sort();
+        seasons
+    }
 
-        players.into_iter().map(|ptag| {
-            let cwl_stars: usize = self
-                .cwl
-                .wars
-                .iter()
-                .map(|war| {
-                    war.members
-                        .get(&ptag)
-                        .map(|mstats| mstats.attacks.iter().map(|a| a.stars).sum::<usize>())
-                        .unwrap_or(0)
-                })
-                .sum();
+    /// The Seasons of the Clan that actually contain Stats, ordered chronologically
+    pub fn seasons_with_data(&self, tag: &ClanTag) -> Vec<Season> {
+        let mut seasons: Vec<Season> = self
+            .clans
+            .get(tag)
+            .into_iter()
+            .flat_map(|seasons| seasons.iter())
+            .filter(|(_, stats)| !stats.is_empty())
+            .map(|(season, _)| season.clone())
+            .collect();
+        seasons.sort();
+        seasons
+    }
 
-            let war_stars: usize = self
-                .wars
-                .values()
-                .map(|war| {
-                    war.members
-                        .get(&ptag)
-                        .map(|mstats| mstats.attacks.iter().map(|att| att.stars).sum::<usize>())
-                        .unwrap_or(0)
-                })
-                .sum();
+    pub fn get(&self, tag: &ClanTag, season: &Season) -> Option<&ClanStorage> {
+        self.clans.get(tag).and_then(|s| s.get(season))
+    }
 
-            let raid_loot: usize = self
-                .raid_weekend
-                .values()
-                .map(|raid| {
-                    raid.members
-                        .get(&ptag)
-                        .map(|rstats| rstats.looted)
-                        .unwrap_or(0)
-                })
-                .sum();
+    /// The Summary of the Player summed up over all the given Seasons of the Clan, Seasons
+    /// without any Stats for the Player are counted as zero
+    pub fn player_summary_across(
+        &self,
+        clan: &ClanTag,
+        seasons: &[Season],
+        player: &PlayerTag,
+    ) -> Option<PlayerSummary> {
+        let stored = self.clans.get(clan)?;
 
-            let games_score = self
-                .games
-                .get(&ptag)
-                .map(|s| s.end_score - s.start_score.unwrap_or(s.end_score))
-                .unwrap_or(0);
+        let summary = seasons
+            .iter()
+            .filter_map(|season| stored.get(season))
+            .filter_map(|stats| {
+                stats
+                    .players_summary()
+                    .find(|(tag, _)| tag == player)
+                    .map(|(_, summary)| summary)
+            })
+            .fold(PlayerSummary::default(), |total, summary| PlayerSummary {
+                cwl_stars: total.cwl_stars.saturating_add(summary.cwl_stars),
+                war_stars: total.war_stars.saturating_add(summary.war_stars),
+                raid_loot: total.raid_loot.saturating_add(summary.raid_loot),
+                games_score: total.games_score.saturating_add(summary.games_score),
+            });
 
-            (
-                ptag,
-                PlayerSummary {
-                    cwl_stars,
-                    war_stars,
-                    raid_loot,
-                    games_score,
-                },
+        Some(summary)
+    }
+
+    /// Renames the Player in every Season of the Clan, see [`ClanStorage::rename_player`]
+    pub fn rename_player(
+        &mut self,
+        clan: &ClanTag,
+        from: &PlayerTag,
+        to: &PlayerTag,
+    ) -> Result<(), IngestError> {
+        let seasons = self.clans.get_mut(clan).ok_or(IngestError::UnknownClan)?;
+        self.player_index = OnceLock::new();
+
+        for stats in seasons.values_mut() {
+            stats.rename_player(from, to);
+        }
+
+        Ok(())
+    }
+
+    /// Uses the last recorded Clan Games Score of every Player in the `from` Season as the
+    /// starting Score in the `to` Season
+    pub fn carry_forward_games(
+        &mut self,
+        clan: &ClanTag,
+        from: &Season,
+        to: &Season,
+    ) -> Result<(), IngestError> {
+        let previous: Vec<(PlayerTag, usize)> = self
+            .get(clan, from)
+            .map(|stats| {
+                stats
+                    .games
+                    .iter()
+                    .map(|(tag, games)| (tag.clone(), games.end_score))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let target = self.get_mut(clan, to).ok_or(IngestError::UnknownClan)?;
+        target.ensure_open()?;
+
+        for (tag, end_score) in previous {
+            let entry = target.games.entry(tag).or_insert(PlayerGamesStats {
+                start_score: None,
+                end_score,
+            });
+            entry.start_score = Some(end_score);
+            entry.end_score = entry.end_score.max(end_score);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the Clans the given Player is tracked in for the Season, sorted by their Tag
+    pub fn player_clan(&self, player: &PlayerTag, season: &Season) -> Vec<ClanTag> {
+        let index = self.player_index.get_or_init(|| {
+            let mut index: HashMap<Season, HashMap<PlayerTag, Vec<ClanTag>>> = HashMap::new();
+
+            for (clan, seasons) in self.clans.iter() {
+                for (season, stats) in seasons.iter() {
+                    let season_index = index.entry(season.clone()).or_default();
+                    for ptag in stats.player_tags() {
+                        season_index.entry(ptag).or_default().push(clan.clone());
+                    }
+                }
+            }
+
+            for players in index.values_mut() {
+                for clans in players.values_mut() {
+                    clans.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+                }
+            }
+
+            index
+        });
+
+        index
+            .get(season)
+            .and_then(|players| players.get(player))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Looks up a single Value using a path like `clan/#ABC/2024-03/player/#DEF/war_stars`,
+    /// supported fields are `name`, `cwl_stars`, `war_stars`, `raid_loot` and `games_score`
+    pub fn query(&self, path: &str) -> Option<serde_json::Value> {
+        let parts: Vec<&str> = path.trim_matches('/').split('/').collect();
+        let (raw_clan, raw_season, raw_player, field) = match parts.as_slice() {
+            ["clan", clan, season, "player", player, field] => (*clan, *season, *player, *field),
+            _ => return None,
+        };
+
+        let season: Season =
+            serde_json::from_value(serde_json::Value::String(raw_season.to_string())).ok()?;
+        let clan = self.get(&ClanTag(raw_clan.to_string()), &season)?;
+        let player = PlayerTag(raw_player.to_string());
+
+        if field == "name" {
+            return clan.player_names.get(&player).cloned().map(Into::into);
+        }
+
+        let (_, summary) = clan.players_summary().find(|(tag, _)| tag == &player)?;
+        let value = match field {
+            "cwl_stars" => summary.cwl_stars,
+            "war_stars" => summary.war_stars,
+            "raid_loot" => summary.raid_loot,
+            "games_score" => summary.games_score,
+            _ => return None,
+        };
+
+        Some(value.into())
+    }
+
+    /// Serializes the Storage with all the Maps sorted by their Keys, so the same Data always
+    /// produces the same Bytes
+    pub fn to_canonical_vec(&self) -> Result<Vec<u8>, StorageError> {
+        // serde_json::Value stores Objects in a BTreeMap, which sorts the HashMap entries
+        let value = serde_json::to_value(self)?;
+        Ok(serde_json::to_vec(&value)?)
+    }
+
+    /// A stable Hash (64-bit FNV-1a) over the canonical serialization of the Storage
+    pub fn checksum(&self) -> u64 {
+        let content = self
+            .to_canonical_vec()
+            .expect("The Storage only contains serializable Data");
+
+        fnv1a(&content)
+    }
+
+    /// Like [`Storage::checksum`], but only over the Stats, ignoring the Revision
+    fn stats_checksum(&self) -> u64 {
+        // serde_json::Value stores Objects in a BTreeMap, which sorts the HashMap entries
+        let content = serde_json::to_value(&self.clans)
+            .and_then(|value| serde_json::to_vec(&value))
+            .expect("The Storage only contains serializable Data");
+
+        fnv1a(&content)
+    }
+
+    /// The Number of Bytes the Storage will take up when saved
+    pub fn serialized_len(&self) -> Result<usize, StorageError> {
+        Ok(self.to_canonical_vec()?.len())
+    }
+
+    /// Imports the flat `{ tag: ClanStorage }` Format of earlier Versions, which did not separate
+    /// the Stats by Season, placing the Stats of every Clan in the given Season
+    pub fn import_legacy_flat(json: &str, season: Season) -> Result<Self, StorageError> {
+        let legacy: HashMap<ClanTag, ClanStorage> = serde_json::from_str(json)?;
+
+        Ok(Self {
+            version: STORAGE_VERSION,
+            revision: 0,
+            clans: legacy
+                .into_iter()
+                .map(|(tag, stats)| (tag, [(season.clone(), stats)].into_iter().collect()))
+                .collect(),
+            saved_checksum: None,
+            player_index: OnceLock::new(),
+        })
+    }
+
+    /// Parses the Storage from either plain or gzip compressed JSON, returning an Error for any
+    /// malformed Input instead of panicking
+    pub fn load_lenient(content: &[u8]) -> Result<Self, StorageError> {
+        let mut storage: Self = if gzip::is_gzip(content) {
+            let content = gzip::decompress(content).map_err(StorageError::Compression)?;
+            serde_json::from_slice(&content)?
+        } else {
+            serde_json::from_slice(content)?
+        };
+        storage.migrate();
+        storage.saved_checksum = Some(storage.stats_checksum());
+
+        Ok(storage)
+    }
+
+    /// The Entry Point for fuzzing, which feeds the Input through loading and then uses the
+    /// loaded Storage like the Bot would, none of which should ever panic
+    #[cfg(fuzz)]
+    pub fn fuzz(content: &[u8]) {
+        let mut storage = match Self::load_lenient(content) {
+            Ok(s) => s,
+            Err(_) => return,
+        };
+
+        let _ = storage.validate_all();
+        let _ = storage.checksum();
+        for (_, _, stats) in storage.iter() {
+            let _ = stats.average_score(&crate::ScoreConfig::default());
+            let _ = stats.participation_rates();
+        }
+        let _ = storage.heal();
+    }
+
+    pub async fn load(store: &mut dyn StorageBackend) -> Result<Self, StorageError> {
+        let content = store.load().await?;
+        let mut storage: Self = serde_json::from_slice(&content)?;
+        storage.migrate();
+        storage.saved_checksum = Some(storage.stats_checksum());
+
+        Ok(storage)
+    }
+
+    /// Loads only the Stats of the Clan in the Season, without deserializing the Stats of all
+    /// the other Clans and Seasons, [`None`] if there are no Stats stored for them
+    pub async fn load_clan_season(
+        store: &mut dyn StorageBackend,
+        clan: &ClanTag,
+        season: &Season,
+    ) -> Result<Option<ClanStorage>, StorageError> {
+        let content = store.load().await?;
+        let mut raw: serde_json::Value = serde_json::from_slice(&content)?;
+
+        let seasons = match raw
+            .get_mut("clans")
+            .and_then(|clans| clans.get_mut(&clan.0))
+            .and_then(serde_json::Value::as_object_mut)
+        {
+            Some(seasons) => seasons,
+            None => return Ok(None),
+        };
+
+        // The Keys are parsed like when loading everything, so they do not have to be canonical
+        let stats = seasons
+            .iter_mut()
+            .find(|(key, _)| key.parse::<Season>().ok().as_ref() == Some(season))
+            .map(|(_, stats)| stats.take());
+
+        match stats {
+            Some(stats) => Ok(Some(serde_json::from_value(stats)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Loads the Storage, or starts with an empty one if nothing is stored yet, runs the Update
+    /// and then saves the Storage again, but only if the Update succeeded and actually changed
+    /// the Data. Any other Error while loading is returned without saving anything
+    pub async fn apply<F, E>(store: &mut dyn StorageBackend, f: F) -> Result<Self, ApplyError<E>>
+    where
+        F: FnOnce(&mut Storage) -> Result<(), E>,
+    {
+        let mut storage = match Self::load(store).await {
+            Ok(storage) => storage,
+            Err(StorageError::NotFound) => Self::empty(),
+            Err(e) => return Err(ApplyError::Load(e)),
+        };
+        let before = storage.checksum();
+
+        f(&mut storage).map_err(ApplyError::Update)?;
+
+        if storage.checksum() != before {
+            storage.save(store).await.map_err(ApplyError::Save)?;
+        }
+
+        Ok(storage)
+    }
+
+    /// The Number of times the Storage has been saved with changed Stats
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Writes the canonical serialization of the Storage to the Backend, increasing the Revision
+    /// only if the Stats changed since they were last loaded or saved. Saving unchanged Stats
+    /// therefore writes the same Bytes, which lets Backends skip identical Uploads
+    pub async fn save(&mut self, store: &mut dyn StorageBackend) -> Result<(), StorageError> {
+        let checksum = self.stats_checksum();
+        if self.saved_checksum != Some(checksum) {
+            self.revision = self.revision.saturating_add(1);
+        }
+
+        let content = self.to_canonical_vec()?;
+        store.write(content).await?;
+        self.saved_checksum = Some(checksum);
+
+        Ok(())
+    }
+
+    /// Saves the Storage like [`Storage::save`], but if someone else changed the stored Content
+    /// in the meantime, it is loaded again and merged into this Storage using
+    /// [`Storage::merge`] before retrying the Save once
+    pub async fn save_merging(
+        &mut self,
+        store: &mut dyn StorageBackend,
+    ) -> Result<(), StorageError> {
+        match self.save(store).await {
+            Err(StorageError::Conflict) => {
+                tracing::warn!("Stored Content was changed by someone else, merging");
+
+                let stored = Self::load(store).await?;
+                self.merge(stored);
+                self.save(store).await
+            }
+            result => result,
+        }
+    }
+}
+
+/// The Summary of a single Player, serialized together with its normalized Scores using the
+/// default [`ScoreConfig`](crate::ScoreConfig)
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct PlayerSummary {
+    pub cwl_stars: usize,
+    pub war_stars: usize,
+    pub raid_loot: usize,
+    pub games_score: usize,
+}
+
+impl ClanStorage {
+    /// Whether there are no Stats recorded at all
+    pub fn is_empty(&self) -> bool {
+        self.cwl.wars.is_empty()
+            && self.wars.is_empty()
+            && self.games.is_empty()
+            && self.raid_weekend.is_empty()
+            && self.player_names.is_empty()
+    }
+
+    /// Closes the Season, freezing the current Stats
+    pub fn close(&mut self) {
+        self.closed = true;
+    }
+
+    /// Reopens a previously closed Season, allowing it to be updated again
+    pub fn reopen(&mut self) {
+        self.closed = false;
+    }
+
+    /// Marks the Stats as refreshed at the given Time
+    pub fn touch(&mut self, now: Time) {
+        self.last_updated = Some(now);
+    }
+
+    /// Whether the Stats have not been refreshed in more than `max_age_days` Days, Stats that
+    /// were never refreshed are always stale
+    pub fn is_stale(&self, now: Time, max_age_days: u32) -> bool {
+        let last_updated = match self.last_updated.and_then(|time| time.to_date()) {
+            Some(date) => date,
+            None => return true,
+        };
+        let now = match now.to_date() {
+            Some(date) => date,
+            None => return true,
+        };
+
+        (now - last_updated).num_days() > i64::from(max_age_days)
+    }
+
+    fn ensure_open(&self) -> Result<(), IngestError> {
+        if self.closed {
+            return Err(IngestError::SeasonClosed);
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the known Player names with the given ones
+    pub fn ingest_names<I>(&mut self, names: I) -> Result<(), IngestError>
+    where
+        I: IntoIterator<Item = (PlayerTag, String)>,
+    {
+        self.ensure_open()?;
+
+        self.player_names.clear();
+        self.player_names.extend(names);
+
+        Ok(())
+    }
+
+    /// Stores the War, replacing any previous entry for the same start time
+    pub fn ingest_war(&mut self, mut war: WarStats) -> Result<(), IngestError> {
+        self.ensure_open()?;
+
+        war.members
+            .values_mut()
+            .for_each(MemberWarStats::sort_attacks);
+        self.wars.insert(war.start_time, war);
+
+        Ok(())
+    }
+
+    /// Updates the CWL round with the given Members, a Member without Attacks keeps any
+    /// previously recorded ones
+    pub fn ingest_cwl_round(
+        &mut self,
+        war_tag: WarTag,
+        round: u8,
+        members: HashMap<PlayerTag, MemberWarStats>,
+    ) -> Result<(), IngestError> {
+        self.ensure_open()?;
+
+        let cwl_stats = match self.cwl.war_mut(&war_tag, round) {
+            Some(stats) => stats,
+            None => {
+                self.cwl.wars.push(CwlWarStats::default());
+                self.cwl.wars.last_mut().expect("A War was just pushed")
+            }
+        };
+        cwl_stats.war_tag = Some(war_tag);
+        cwl_stats.round = round;
+
+        for (tag, stats) in members {
+            let member_stats = cwl_stats
+                .members
+                .entry(tag)
+                .or_insert_with(|| MemberWarStats {
+                    attacks: Vec::new(),
+                });
+
+            if !stats.attacks.is_empty() {
+                member_stats.attacks = stats.attacks;
+                member_stats.sort_attacks();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Records the current total Clan Games Score of the Player, the first recorded Score
+    /// becomes the starting point for the Season
+    pub fn ingest_games(&mut self, player: PlayerTag, score: usize) -> Result<(), IngestError> {
+        self.ensure_open()?;
+
+        let entry = self.games.entry(player).or_insert(PlayerGamesStats {
+            start_score: Some(score),
+            end_score: score,
+        });
+        if entry.start_score.is_none() {
+            entry.start_score = Some(score);
+        }
+        entry.end_score = score;
+
+        Ok(())
+    }
+
+    /// Stores the Raid Weekend, replacing any previous entry for the same start time
+    pub fn ingest_raid(&mut self, raid: RaidWeekendStats) -> Result<(), IngestError> {
+        self.ensure_open()?;
+
+        self.raid_weekend.insert(raid.start_time, raid);
+
+        Ok(())
+    }
+
+    /// Merges the Stats of the other Storage for the same Season into this one, keeping the
+    /// best Result whenever both contain data for the same thing and the newer `last_updated`
+    pub fn merge_from(&mut self, other: &ClanStorage) {
+        self.last_updated = self.last_updated.max(other.last_updated);
+
+        for (index, round) in other.cwl.wars.iter().enumerate() {
+            let existing = match round.war_tag.as_ref() {
+                Some(war_tag) => self.cwl.war_mut(war_tag, round.round),
+                None => self.cwl.wars.get_mut(index),
+            };
+            match existing {
+                Some(existing) => merge_members(&mut existing.members, &round.members),
+                None => self.cwl.wars.push(round.clone()),
+            }
+        }
+
+        for (time, war) in other.wars.iter() {
+            match self.wars.get_mut(time) {
+                Some(existing) => merge_members(&mut existing.members, &war.members),
+                None => {
+                    self.wars.insert(*time, war.clone());
+                }
+            }
+        }
+
+        for (tag, games) in other.games.iter() {
+            match self.games.get_mut(tag) {
+                Some(existing) => merge_games(existing, games),
+                None => {
+                    self.games.insert(tag.clone(), games.clone());
+                }
+            }
+        }
+
+        for (time, raid) in other.raid_weekend.iter() {
+            let existing = self
+                .raid_weekend
+                .entry(*time)
+                .or_insert_with(|| raid.clone());
+            for (tag, member) in raid.members.iter() {
+                let entry = existing
+                    .members
+                    .entry(tag.clone())
+                    .or_insert_with(|| member.clone());
+                if member.looted > entry.looted {
+                    *entry = member.clone();
+                }
+            }
+        }
+
+        for (tag, name) in other.player_names.iter() {
+            self.player_names
+                .entry(tag.clone())
+                .or_insert_with(|| name.clone());
+        }
+    }
+
+    /// Moves all the Stats of the `from` Player to the `to` Player, merging them with the Stats
+    /// already recorded for `to` like [`ClanStorage::merge_from`] does
+    pub fn rename_player(&mut self, from: &PlayerTag, to: &PlayerTag) {
+        if from == to {
+            return;
+        }
+
+        let merge_war = |existing: &mut MemberWarStats, stats: MemberWarStats| {
+            if stats.attacks.len() > existing.attacks.len() {
+                *existing = stats;
+            }
+        };
+        for war in self.cwl.wars.iter_mut() {
+            rename_key(&mut war.members, from, to, merge_war);
+        }
+        for war in self.wars.values_mut() {
+            rename_key(&mut war.members, from, to, merge_war);
+        }
+
+        for raid in self.raid_weekend.values_mut() {
+            rename_key(&mut raid.members, from, to, |existing, member| {
+                if member.looted > existing.looted {
+                    *existing = member;
+                }
+            });
+        }
+
+        rename_key(&mut self.games, from, to, |existing, games| {
+            merge_games(existing, &games)
+        });
+        rename_key(&mut self.player_names, from, to, |_, _| {});
+    }
+
+    /// Imports Player names from `tag,name` rows, returning the line number and content of
+    /// every row that could not be imported
+    pub fn import_names_csv(&mut self, csv: &str) -> Vec<(usize, String)> {
+        let mut invalid = Vec::new();
+
+        for (index, line) in csv.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let (raw_tag, raw_name) = match line.split_once(',') {
+                Some(parts) => parts,
+                None => {
+                    invalid.push((index + 1, line.to_string()));
+                    continue;
+                }
+            };
+
+            if index == 0 && raw_tag.trim().eq_ignore_ascii_case("tag") {
+                continue;
+            }
+
+            let name = raw_name.trim();
+            match crate::tags::parse_tag(raw_tag) {
+                Ok(tag) if !name.is_empty() => {
+                    self.player_names.insert(PlayerTag(tag), name.to_string());
+                }
+                _ => {
+                    invalid.push((index + 1, line.to_string()));
+                }
+            }
+        }
+
+        invalid
+    }
+
+    /// The Standard Deviation of the Stars over all the War and CWL Attacks of the Player, so a
+    /// lower Value means a more consistent Player, [`None`] with less than two Attacks
+    pub fn star_consistency(&self, player: &PlayerTag) -> Option<f64> {
+        let stars: Vec<f64> = self
+            .wars
+            .values()
+            .map(|war| &war.members)
+            .chain(self.cwl.wars.iter().map(|war| &war.members))
+            .filter_map(|members| members.get(player))
+            .flat_map(|stats| stats.attacks.iter().map(|attack| attack.stars as f64))
+            .collect();
+
+        if stars.len() < 2 {
+            return None;
+        }
+
+        let mean = stars.iter().sum::<f64>() / stars.len() as f64;
+        let variance = stars.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / stars.len() as f64;
+
+        Some(variance.sqrt())
+    }
+
+    /// Every regular War of the Season ordered by Time
+    pub fn war_history(&self) -> Vec<WarHistoryRow> {
+        self.wars
+            .iter()
+            .map(|(time, war)| WarHistoryRow {
+                time: *time,
+                opponent: war.opponent.clone(),
+                result: war.result,
+                stars: war.net_new_stars().values().sum(),
+                destruction: war.destruction(),
+            })
+            .collect()
+    }
+
+    /// The Number of Wins, Losses and Ties of the regular Wars, Wars without a known Result
+    /// are not counted
+    pub fn war_record(&self) -> (usize, usize, usize) {
+        self.wars.values().filter_map(|war| war.result).fold(
+            (0, 0, 0),
+            |(wins, losses, ties), result| match result {
+                WarResult::Win => (wins + 1, losses, ties),
+                WarResult::Loss => (wins, losses + 1, ties),
+                WarResult::Tie => (wins, losses, ties + 1),
+            },
+        )
+    }
+
+    /// The fastest three Star Attack of every Player across the regular Wars, sorted by the
+    /// Duration with the fastest first
+    pub fn fastest_three_stars(&self, limit: usize) -> Vec<(PlayerTag, usize)> {
+        let mut fastest: HashMap<&PlayerTag, usize> = HashMap::new();
+
+        for (tag, stats) in self.wars.values().flat_map(|war| war.members.iter()) {
+            for attack in stats.attacks.iter().filter(|attack| attack.stars == 3) {
+                let entry = fastest.entry(tag).or_insert(attack.duration);
+                *entry = (*entry).min(attack.duration);
+            }
+        }
+
+        let mut result: Vec<(PlayerTag, usize)> = fastest
+            .into_iter()
+            .map(|(tag, duration)| (tag.clone(), duration))
+            .collect();
+        result.sort_unstable_by(|(t1, d1), (t2, d2)| d1.cmp(d2).then_with(|| t1.0.cmp(&t2.0)));
+        result.truncate(limit);
+
+        result
+    }
+
+    /// The average Results of the Attacks in regular and CWL Wars, grouped by the Town Hall
+    /// Levels of the Attacker and Defender
+    pub fn attacks_by_matchup(&self) -> HashMap<(u8, u8), StarStats> {
+        let mut totals: HashMap<(u8, u8), (usize, usize, usize)> = HashMap::new();
+
+        let attacks = self
+            .wars
+            .values()
+            .flat_map(|war| war.members.values())
+            .chain(self.cwl.wars.iter().flat_map(|war| war.members.values()))
+            .flat_map(|member| member.attacks.iter());
+        for attack in attacks {
+            let (attacker, defender) = match (attack.attacker_townhall, attack.defender_townhall) {
+                (Some(a), Some(d)) => (a, d),
+                _ => continue,
+            };
+
+            let entry = totals.entry((attacker, defender)).or_insert((0, 0, 0));
+            entry.0 += 1;
+            entry.1 += attack.stars;
+            entry.2 += attack.destruction;
+        }
+
+        totals
+            .into_iter()
+            .map(|(matchup, (attacks, stars, destruction))| {
+                (
+                    matchup,
+                    StarStats {
+                        attacks,
+                        average_stars: stars as f64 / attacks as f64,
+                        average_destruction: destruction as f64 / attacks as f64,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// All the Players we have any kind of data for in this Season
+    pub fn player_tags(&self) -> HashSet<PlayerTag> {
+        let mut players: HashSet<PlayerTag> = self.player_names.keys().cloned().collect();
+
+        players.extend(
+            self.cwl
+                .wars
+                .iter()
+                .flat_map(|war| war.members.keys().cloned()),
+        );
+        players.extend(
+            self.wars
+                .values()
+                .flat_map(|war| war.members.keys().cloned()),
+        );
+        players.extend(
+            self.raid_weekend
+                .values()
+                .flat_map(|raid| raid.members.keys().cloned()),
+        );
+        players.extend(self.games.keys().cloned());
+
+        players
+    }
+
+    pub fn players_summary(&self) -> impl Iterator<Item = (PlayerTag, PlayerSummary)> + '_ {
+        self.player_tags().into_iter().map(|ptag| {
+            let cwl_stars = self.cwl.wars.iter().map(|war| {
+                let stars = war
+                    .members
+                    .get(&ptag)
+                    .map(|mstats| saturating_sum(mstats.attacks.iter().map(|a| a.stars)))
+                    .unwrap_or(0);
+
+                if stars > CWL_MAX_STARS_PER_ROUND {
+                    tracing::warn!(
+                        "Clamping {} CWL Stars in a single Round for {:?}",
+                        stars,
+                        ptag
+                    );
+                }
+
+                stars.min(CWL_MAX_STARS_PER_ROUND)
+            });
+            let cwl_stars = saturating_sum(cwl_stars);
+
+            let war_stars = saturating_sum(self.wars.values().map(|war| {
+                war.members
+                    .get(&ptag)
+                    .map(|mstats| mstats.effective_stars())
+                    .unwrap_or(0)
+            }));
+
+            let raid_loot = saturating_sum(self.raid_weekend.values().map(|raid| {
+                raid.members
+                    .get(&ptag)
+                    .map(|rstats| rstats.looted)
+                    .unwrap_or(0)
+            }));
+
+            // The start Score can be larger, if the Player was reset or the data is corrupted
+            let games_score = self
+                .games
+                .get(&ptag)
+                .map(|s| {
+                    s.end_score
+                        .saturating_sub(s.start_score.unwrap_or(s.end_score))
+                })
+                .unwrap_or(0);
+
+            (
+                ptag,
+                PlayerSummary {
+                    cwl_stars,
+                    war_stars,
+                    raid_loot,
+                    games_score,
+                },
             )
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn season() -> Season {
+        Season {
+            year: 2023,
+            month: 8,
+        }
+    }
+
+    #[test]
+    fn seasons_with_data() {
+        let clan = ClanTag("#2PP".to_string());
+        let mut storage = Storage::new_with_clans([clan.clone()]);
+
+        let seasons = [
+            Season::from_ymd(2023, 9).unwrap(),
+            Season::from_ymd(2022, 12).unwrap(),
+            Season::from_ymd(2023, 1).unwrap(),
+        ];
+        for season in seasons.iter() {
+            storage.get_mut(&clan, season).unwrap();
+        }
+        for season in seasons[..2].iter() {
+            storage
+                .get_mut(&clan, season)
+                .unwrap()
+                .ingest_games(PlayerTag("#2QQ".to_string()), 100)
+                .unwrap();
+        }
+
+        let mut all = storage.seasons(&clan);
+        all.sort();
+        assert_eq!(
+            vec![seasons[1].clone(), seasons[2].clone(), seasons[0].clone()],
+            all
+        );
+        assert_eq!(
+            vec![seasons[1].clone(), seasons[0].clone()],
+            storage.seasons_with_data(&clan)
+        );
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn load_lenient_never_panics(
+            content in proptest::collection::vec(proptest::num::u8::ANY, 0..512)
+        ) {
+            let _ = Storage::load_lenient(&content);
+        }
+
+        #[test]
+        fn load_lenient_seasons(year in 0usize..10000, month in 0usize..100) {
+            let content = serde_json::json!({
+                "clans": {
+                    "#2PP": {
+                        format!("{:04}-{:02}", year, month): ClanStorage::default(),
+                    },
+                },
+            });
+
+            match Storage::load_lenient(&serde_json::to_vec(&content).unwrap()) {
+                Ok(storage) => {
+                    proptest::prop_assert!((1..=12).contains(&month));
+                    let seasons = storage.seasons(&ClanTag("#2PP".to_string()));
+                    let _ = seasons[0].previous();
+                }
+                Err(_) => proptest::prop_assert!(!(1..=12).contains(&month)),
+            }
+        }
+
+        #[test]
+        fn summary_never_overflows(
+            start in proptest::num::usize::ANY,
+            end in proptest::num::usize::ANY,
+            stars in proptest::num::usize::ANY
+        ) {
+            let player = PlayerTag("#2PP".to_string());
+            let start_time = Time {
+                year: 2023,
+                month: 8,
+                day: 1,
+            };
+            let attack = WarAttack {
+                destruction: 100,
+                stars,
+                duration: 0,
+                target: None,
+                order: 0,
+                attacker_townhall: None,
+                defender_townhall: None,
+            };
+
+            let mut clan = ClanStorage::default();
+            clan.player_names.insert(player.clone(), "Player".to_string());
+            clan.games.insert(
+                player.clone(),
+                PlayerGamesStats {
+                    start_score: Some(start),
+                    end_score: end,
+                },
+            );
+            clan.ingest_war(WarStats {
+                start_time,
+                members: [(
+                    player.clone(),
+                    MemberWarStats {
+                        attacks: vec![attack.clone(), attack],
+                    },
+                )]
+                .into_iter()
+                .collect(),
+                attacks_per_member: 2,
+                opponent: None,
+                result: None,
+            })
+            .unwrap();
+
+            let (_, summary) = clan.players_summary().next().unwrap();
+            proptest::prop_assert_eq!(end.saturating_sub(start), summary.games_score);
+            let _ = summary.total_score();
+        }
+    }
+
+    #[test]
+    fn import_legacy_flat() {
+        let legacy = r##"{
+            "#2PP": {
+                "cwl": { "wars": [] },
+                "wars": {},
+                "games": { "#8YY": { "start_score": 100, "end_score": 600 } },
+                "raid_weekend": {},
+                "player_names": { "#8YY": "Player" }
+            },
+            "#2QQ": {
+                "cwl": { "wars": [] },
+                "wars": {},
+                "games": {},
+                "raid_weekend": {},
+                "player_names": {}
+            }
+        }"##;
+
+        let storage = Storage::import_legacy_flat(legacy, season()).unwrap();
+
+        let first = ClanTag("#2PP".to_string());
+        assert_eq!(vec![season()], storage.seasons(&first));
+        assert_eq!(
+            vec![season()],
+            storage.seasons(&ClanTag("#2QQ".to_string()))
+        );
+        let stats = storage.get(&first, &season()).unwrap();
+        assert_eq!(
+            Some(&"Player".to_string()),
+            stats.player_names.get(&PlayerTag("#8YY".to_string()))
+        );
+        assert_eq!(500, stats.players_summary().next().unwrap().1.games_score);
+
+        assert!(Storage::import_legacy_flat("[]", season()).is_err());
+    }
+
+    #[test]
+    fn season_from_ymd() {
+        assert_eq!(
+            Ok(Season {
+                year: 2024,
+                month: 12
+            }),
+            Season::from_ymd(2024, 12)
+        );
+        assert_eq!(Err(SeasonError::InvalidMonth(0)), Season::from_ymd(2024, 0));
+        assert_eq!(
+            Err(SeasonError::InvalidMonth(13)),
+            Season::from_ymd(2024, 13)
+        );
+    }
+
+    #[test]
+    fn season_next() {
+        assert_eq!(
+            Season {
+                year: 2024,
+                month: 7
+            },
+            Season {
+                year: 2024,
+                month: 6
+            }
+            .next()
+        );
+        assert_eq!(
+            Season {
+                year: 2025,
+                month: 1
+            },
+            Season {
+                year: 2024,
+                month: 12
+            }
+            .next()
+        );
+
+        let season = season();
+        assert_eq!(season, season.next().previous());
+    }
+
+    #[test]
+    fn season_range_iter() {
+        let season = season();
+        assert_eq!(
+            vec![season.clone()],
+            Season::range(season.clone(), season.clone()).collect::<Vec<_>>()
+        );
+
+        assert_eq!(
+            vec![
+                Season::from_ymd(2024, 12).unwrap(),
+                Season::from_ymd(2025, 1).unwrap(),
+                Season::from_ymd(2025, 2).unwrap(),
+            ],
+            Season::range(
+                Season::from_ymd(2024, 12).unwrap(),
+                Season::from_ymd(2025, 2).unwrap()
+            )
+            .collect::<Vec<_>>()
+        );
+
+        assert_eq!(0, Season::range(season.next(), season).count());
+
+        let invalid = Season {
+            year: 2024,
+            month: 13,
+        };
+        assert_eq!(
+            vec![
+                Season::from_ymd(2024, 11).unwrap(),
+                Season::from_ymd(2024, 12).unwrap(),
+            ],
+            Season::range(Season::from_ymd(2024, 11).unwrap(), invalid).collect::<Vec<_>>()
+        );
+        let last = Season {
+            year: usize::MAX,
+            month: 12,
+        };
+        let after_last = Season {
+            year: usize::MAX,
+            month: 13,
+        };
+        assert_eq!(
+            vec![last.clone()],
+            Season::range(last, after_last).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn season_range() {
+        let range: SeasonRange =
+            serde_json::from_str(r#"{ "from": "2024-11", "to": "2025-02" }"#).unwrap();
+
+        assert_eq!(
+            vec![
+                Season::from_ymd(2024, 11).unwrap(),
+                Season::from_ymd(2024, 12).unwrap(),
+                Season::from_ymd(2025, 1).unwrap(),
+                Season::from_ymd(2025, 2).unwrap(),
+            ],
+            range.seasons()
+        );
+        assert_eq!(
+            range,
+            serde_json::from_str(&serde_json::to_string(&range).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn season_from_str() {
+        for raw in ["2024-01", "2024-12", "0999-07"] {
+            let season = Season::from_str(raw).unwrap();
+            assert_eq!(raw, season.to_string());
+            assert_eq!(Ok(season.clone()), Season::from_str(&season.to_string()));
+        }
+
+        assert_eq!(
+            Err(SeasonError::MissingSeparator),
+            Season::from_str("202401")
+        );
+        assert_eq!(
+            Err(SeasonError::InvalidNumber("Jan".to_string())),
+            Season::from_str("2024-Jan")
+        );
+        assert_eq!(
+            Err(SeasonError::InvalidMonth(13)),
+            Season::from_str("2024-13")
+        );
+        assert_eq!(
+            Err(SeasonError::InvalidMonth(0)),
+            Season::from_str("2024-00")
+        );
+    }
+
+    #[test]
+    fn season_ordering() {
+        let mut seasons: Vec<Season> = ["2025-02", "2024-12", "2025-01", "2024-11"]
+            .into_iter()
+            .map(|raw| serde_json::from_str(&format!("\"{}\"", raw)).unwrap())
+            .collect();
+        seasons.sort();
+
+        let serialized: Vec<String> = seasons
+            .iter()
+            .map(|season| serde_json::to_string(season).unwrap())
+            .collect();
+        assert_eq!(
+            vec!["\"2024-11\"", "\"2024-12\"", "\"2025-01\"", "\"2025-02\""],
+            serialized
+        );
+        assert!(
+            Season {
+                year: 2024,
+                month: 12
+            } < Season {
+                year: 2025,
+                month: 1
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_season_forms() {
+        assert_eq!(
+            Season {
+                year: 2024,
+                month: 3
+            },
+            serde_json::from_str::<Season>("\"2024-03\"").unwrap()
+        );
+        assert_eq!(
+            Season {
+                year: 2024,
+                month: 3
+            },
+            serde_json::from_str::<Season>("{ \"year\": 2024, \"month\": 3 }").unwrap()
+        );
+        assert_eq!(
+            "\"2024-03\"",
+            serde_json::to_string(&Season {
+                year: 2024,
+                month: 3
+            })
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn new_with_clans() {
+        let first = ClanTag("#2L99VLJ9P".to_string());
+        let second = ClanTag("#9QQ".to_string());
+
+        let storage = Storage::new_with_clans([first.clone(), second.clone(), first.clone()]);
+
+        assert_eq!(2, storage.clans.len());
+        assert!(storage.clans.contains_key(&first));
+        assert!(storage.clans.contains_key(&second));
+    }
+
+    #[test]
+    fn list_clans_and_seasons() {
+        let first = ClanTag("#2QQ".to_string());
+        let second = ClanTag("#2PP".to_string());
+        let mut storage = Storage::new_with_clans([first.clone(), second.clone()]);
+
+        let seasons = [season().next(), season().previous(), season()];
+        for season in seasons.iter() {
+            storage.get_mut(&first, season).unwrap();
+        }
+
+        assert_eq!(vec![second.clone(), first.clone()], storage.list_clans());
+        assert_eq!(
+            vec![seasons[1].clone(), seasons[2].clone(), seasons[0].clone()],
+            storage.list_seasons(&first)
+        );
+        assert!(storage.list_seasons(&second).is_empty());
+        assert!(storage
+            .list_seasons(&ClanTag("#8QQ".to_string()))
+            .is_empty());
+    }
+
+    #[test]
+    fn remove_clan() {
+        let clan = ClanTag("#2PP".to_string());
+        let mut storage = Storage::new_with_clans([clan.clone()]);
+
+        assert!(!storage.remove_clan(&ClanTag("#2QQ".to_string())));
+        assert!(storage.remove_clan(&clan));
+        assert_eq!(0, storage.clans().count());
+        assert!(!storage.remove_clan(&clan));
+    }
+
+    #[test]
+    fn remove_season() {
+        let clan = ClanTag("#2PP".to_string());
+        let mut storage = Storage::new_with_clans([clan.clone()]);
+        storage.get_mut(&clan, &season()).unwrap();
+
+        assert!(!storage.remove_season(&clan, &season().next()));
+        assert!(!storage.remove_season(&ClanTag("#2QQ".to_string()), &season()));
+        assert!(storage.remove_season(&clan, &season()));
+        assert_eq!(vec![clan.clone()], storage.list_clans());
+        assert!(storage.seasons(&clan).is_empty());
+    }
+
+    #[test]
+    fn prune_seasons_older_than() {
+        let clans = [ClanTag("#2PP".to_string()), ClanTag("#2QQ".to_string())];
+        let seasons = [season().previous(), season(), season().next()];
+
+        let mut storage = Storage::new_with_clans(clans.clone());
+        for clan in clans.iter() {
+            for season in seasons.iter() {
+                storage.get_mut(clan, season).unwrap();
+            }
+        }
+
+        assert_eq!(2, storage.prune_seasons_older_than(season()));
+        for clan in clans.iter() {
+            assert_eq!(seasons[1..].to_vec(), storage.list_seasons(clan));
+        }
+        assert_eq!(0, storage.prune_seasons_older_than(season()));
+    }
+
+    #[test]
+    fn player_summary_across() {
+        let clan = ClanTag("#2PP".to_string());
+        let player = PlayerTag("#2QQ".to_string());
+        let seasons = [season().previous(), season(), season().next()];
+
+        let mut storage = Storage::new_with_clans([clan.clone()]);
+        for (season, stars) in seasons[..2].iter().zip([3, 2]) {
+            storage
+                .get_mut(&clan, season)
+                .unwrap()
+                .ingest_war(WarStats {
+                    start_time: Time {
+                        year: season.year,
+                        month: season.month,
+                        day: 4,
+                    },
+                    members: [(
+                        player.clone(),
+                        MemberWarStats {
+                            attacks: vec![attack("#9LL", 1, stars)],
+                        },
+                    )]
+                    .into_iter()
+                    .collect(),
+                    attacks_per_member: 2,
+                    opponent: None,
+                    result: None,
+                })
+                .unwrap();
+        }
+
+        let summary = storage
+            .player_summary_across(&clan, &seasons, &player)
+            .unwrap();
+        assert_eq!(5, summary.war_stars);
+        assert_eq!(0, summary.games_score);
+
+        assert_eq!(
+            None,
+            storage.player_summary_across(&ClanTag("#8QQ".to_string()), &seasons, &player)
+        );
+    }
+
+    #[test]
+    fn games_score_reset() {
+        let tag = PlayerTag("#2PP".to_string());
+        let mut clan = ClanStorage::default();
+        clan.player_names.insert(tag.clone(), "Player".to_string());
+        clan.games.insert(
+            tag.clone(),
+            PlayerGamesStats {
+                start_score: Some(6000),
+                end_score: 5000,
+            },
+        );
+
+        let summaries: HashMap<_, _> = clan.players_summary().collect();
+        assert_eq!(0, summaries[&tag].games_score);
+    }
+
+    #[test]
+    fn is_stale() {
+        let time = |month, day| Time {
+            year: 2024,
+            month,
+            day,
+        };
+
+        let mut clan = ClanStorage::default();
+        assert!(clan.is_stale(time(1, 1), 7));
+
+        clan.touch(time(1, 28));
+        assert!(!clan.is_stale(time(1, 28), 0));
+        assert!(!clan.is_stale(time(2, 4), 7));
+        assert!(clan.is_stale(time(2, 5), 7));
+
+        let clan: ClanStorage = serde_json::from_str(
+            r#"{"cwl":{"wars":[]},"wars":{},"games":{},"raid_weekend":{},"player_names":{}}"#,
+        )
+        .unwrap();
+        assert_eq!(None, clan.last_updated);
+    }
+
+    #[test]
+    fn summary_without_name() {
+        let tag = PlayerTag("#2PP".to_string());
+        let mut clan = ClanStorage::default();
+        clan.ingest_war(WarStats {
+            start_time: Time {
+                year: 2023,
+                month: 8,
+                day: 4,
+            },
+            members: [(
+                tag.clone(),
+                MemberWarStats {
+                    attacks: vec![attack("#9LL", 1, 3), attack("#9QQ", 2, 2)],
+                },
+            )]
+            .into_iter()
+            .collect(),
+            attacks_per_member: 2,
+            opponent: None,
+            result: None,
+        })
+        .unwrap();
+        assert!(clan.player_names.is_empty());
+
+        let summaries: HashMap<_, _> = clan.players_summary().collect();
+        assert_eq!(5, summaries[&tag].war_stars);
+    }
+
+    #[tokio::test]
+    async fn load_legacy_version() {
+        let mut backend = MemoryStorage::with_content(br##"{ "clans": { "#2PP": {} } }"##.to_vec());
+
+        let storage = Storage::load(&mut backend).await.unwrap();
+        assert_eq!(STORAGE_VERSION, storage.version());
+        assert_eq!(vec![ClanTag("#2PP".to_string())], storage.list_clans());
+    }
+
+    #[tokio::test]
+    async fn load_clan_season() {
+        let clan = ClanTag("#2PP".to_string());
+        let other = ClanTag("#2QQ".to_string());
+        let player = PlayerTag("#8YY".to_string());
+
+        let mut storage = Storage::new_with_clans([clan.clone(), other.clone()]);
+        for (tag, season, name) in [
+            (&clan, season(), "Current"),
+            (&clan, season().previous(), "Previous"),
+            (&other, season(), "Other"),
+        ] {
+            storage
+                .get_mut(tag, &season)
+                .unwrap()
+                .player_names
+                .insert(player.clone(), name.to_string());
+        }
+
+        let mut backend = MemoryStorage::new();
+        storage.save(&mut backend).await.unwrap();
+
+        let stats = Storage::load_clan_season(&mut backend, &clan, &season())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            Some(&"Current".to_string()),
+            stats.player_names.get(&player)
+        );
+
+        assert!(
+            Storage::load_clan_season(&mut backend, &clan, &season().next())
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            Storage::load_clan_season(&mut backend, &ClanTag("#9LL".to_string()), &season())
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn load_errors() {
+        let mut backend = MemoryStorage::new();
+        assert!(matches!(
+            Storage::load(&mut backend).await,
+            Err(StorageError::NotFound)
+        ));
+
+        let mut backend = MemoryStorage::with_content(b"{ not json".to_vec());
+        assert!(matches!(
+            Storage::load(&mut backend).await,
+            Err(StorageError::Serialization(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn exists() {
+        let mut backend = MemoryStorage::new();
+        assert!(!backend.exists().await.unwrap());
+
+        Storage::empty().save(&mut backend).await.unwrap();
+        assert!(backend.exists().await.unwrap());
+
+        struct Unreachable;
+        impl StorageBackend for Unreachable {
+            fn write(
+                &mut self,
+                _: Vec<u8>,
+            ) -> Pin<Box<dyn Future<Output = Result<(), StorageError>> + Send + 'static>>
+            {
+                Box::pin(async { Err(StorageError::Backend("Unreachable".to_string())) })
+            }
+
+            fn load(
+                &mut self,
+            ) -> Pin<Box<dyn Future<Output = Result<Vec<u8>, StorageError>> + Send + 'static>>
+            {
+                Box::pin(async { Err(StorageError::Backend("Unreachable".to_string())) })
+            }
+        }
+        assert!(matches!(
+            Unreachable.exists().await,
+            Err(StorageError::Backend(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn apply_unreadable() {
+        let mut backend = MemoryStorage::with_content(b"not json".to_vec());
+
+        let result = Storage::apply(&mut backend, |storage| {
+            storage.register_clan(ClanTag("#2PP".to_string()));
+            Ok::<_, ()>(())
+        })
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(ApplyError::Load(StorageError::Serialization(_)))
+        ));
+        assert_eq!(Some(b"not json".to_vec()), backend.content());
+    }
+
+    #[tokio::test]
+    async fn apply() {
+        let clan = ClanTag("#2L99VLJ9P".to_string());
+        let mut backend = MemoryStorage::new();
+
+        Storage::apply(&mut backend, |_| Ok::<_, ()>(()))
+            .await
+            .unwrap();
+        assert!(backend.content().is_none());
+
+        let failed = Storage::apply(&mut backend, |storage| {
+            storage.register_clan(clan.clone());
+            Err("failed")
+        })
+        .await;
+        assert!(matches!(failed, Err(ApplyError::Update("failed"))));
+        assert!(backend.content().is_none());
+
+        let storage = Storage::apply(&mut backend, |storage| {
+            storage.register_clan(clan.clone());
+            Ok::<_, ()>(())
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            storage.checksum(),
+            Storage::load(&mut backend).await.unwrap().checksum()
+        );
+    }
+
+    #[tokio::test]
+    async fn save_merging() {
+        /// Rejects the first Write like another Instance changed the Content in the meantime
+        struct ConflictOnce(MemoryStorage, bool);
+        impl StorageBackend for ConflictOnce {
+            fn write(
+                &mut self,
+                content: Vec<u8>,
+            ) -> Pin<Box<dyn Future<Output = Result<(), StorageError>> + Send + 'static>>
+            {
+                if std::mem::replace(&mut self.1, false) {
+                    return Box::pin(async { Err(StorageError::Conflict) });
+                }
+                self.0.write(content)
+            }
+
+            fn load(
+                &mut self,
+            ) -> Pin<Box<dyn Future<Output = Result<Vec<u8>, StorageError>> + Send + 'static>>
+            {
+                self.0.load()
+            }
+        }
+
+        let inner = MemoryStorage::new();
+        Storage::new_with_clans([ClanTag("#2QQ".to_string())])
+            .save(&mut inner.clone())
+            .await
+            .unwrap();
+        let mut backend = ConflictOnce(inner.clone(), true);
+
+        let mut storage = Storage::new_with_clans([ClanTag("#2PP".to_string())]);
+        storage.save_merging(&mut backend).await.unwrap();
+
+        let stored = Storage::load(&mut backend).await.unwrap();
+        assert_eq!(
+            vec![ClanTag("#2PP".to_string()), ClanTag("#2QQ".to_string())],
+            stored.list_clans()
+        );
+    }
+
+    #[tokio::test]
+    async fn save_unchanged() {
+        let mut backend = MemoryStorage::new();
+
+        let mut storage = Storage::new_with_clans([ClanTag("#2PP".to_string())]);
+        storage.save(&mut backend).await.unwrap();
+        let first = backend.content().unwrap();
+        assert_eq!(1, storage.revision());
+
+        storage.save(&mut backend).await.unwrap();
+        assert_eq!(Some(first.clone()), backend.content());
+
+        let mut loaded = Storage::load(&mut backend).await.unwrap();
+        loaded.save(&mut backend).await.unwrap();
+        assert_eq!(Some(first), backend.content());
+        assert_eq!(1, loaded.revision());
+
+        loaded.register_clan(ClanTag("#2QQ".to_string()));
+        loaded.save(&mut backend).await.unwrap();
+        assert_eq!(2, loaded.revision());
+    }
+
+    #[tokio::test]
+    async fn serialized_len() {
+        let clan = ClanTag("#2L99VLJ9P".to_string());
+
+        let mut storage = Storage::new_with_clans([clan.clone()]);
+        storage
+            .get_mut(&clan, &season())
+            .unwrap()
+            .player_names
+            .insert(PlayerTag("#2PP".to_string()), "Player".to_string());
+
+        let mut backend = MemoryStorage::new();
+        storage.save(&mut backend).await.unwrap();
+
+        let written = backend.content().unwrap().len();
+        assert_eq!(written, storage.serialized_len().unwrap());
+    }
+
+    #[test]
+    fn iter_mut() {
+        let first = ClanTag("#2L99VLJ9P".to_string());
+        let second = ClanTag("#9QQ".to_string());
+        let player = PlayerTag("#2PP".to_string());
+
+        let mut storage = Storage::new_with_clans([first.clone(), second.clone()]);
+        for clan in [&first, &second] {
+            storage
+                .get_mut(clan, &season())
+                .unwrap()
+                .player_names
+                .insert(player.clone(), "Player".to_string());
+        }
+        assert_eq!(
+            vec![first.clone(), second.clone()],
+            storage.player_clan(&player, &season())
+        );
+
+        for (_, _, stats) in storage.iter_mut() {
+            stats.player_names.clear();
+        }
+
+        assert!(storage
+            .get(&first, &season())
+            .unwrap()
+            .player_names
+            .is_empty());
+        assert!(storage
+            .get(&second, &season())
+            .unwrap()
+            .player_names
+            .is_empty());
+        assert!(storage.player_clan(&player, &season()).is_empty());
+    }
+
+    #[test]
+    fn query() {
+        let clan = ClanTag("#2L99VLJ9P".to_string());
+        let player = PlayerTag("#2PP".to_string());
+
+        let mut storage = Storage::new_with_clans([clan.clone()]);
+        let stats = storage.get_mut(&clan, &season()).unwrap();
+        stats
+            .player_names
+            .insert(player.clone(), "Player".to_string());
+        stats
+            .ingest_war(WarStats {
+                attacks_per_member: 2,
+                opponent: None,
+                result: None,
+                start_time: Time {
+                    year: 2023,
+                    month: 8,
+                    day: 4,
+                },
+                members: [(
+                    player,
+                    MemberWarStats {
+                        attacks: vec![attack("#8YY", 1, 3), attack("#9LL", 2, 2)],
+                    },
+                )]
+                .into_iter()
+                .collect(),
+            })
+            .unwrap();
+
+        assert_eq!(
+            Some(serde_json::Value::from(5)),
+            storage.query("clan/#2L99VLJ9P/2023-08/player/#2PP/war_stars")
+        );
+        assert_eq!(
+            Some(serde_json::Value::from("Player")),
+            storage.query("clan/#2L99VLJ9P/2023-08/player/#2PP/name")
+        );
+        assert_eq!(
+            None,
+            storage.query("clan/#2L99VLJ9P/2023-08/player/#9QQ/war_stars")
+        );
+        assert_eq!(
+            None,
+            storage.query("clan/#2L99VLJ9P/2023-09/player/#2PP/war_stars")
+        );
+        assert_eq!(None, storage.query("clan/#2L99VLJ9P/2023-08"));
+    }
+
+    #[test]
+    fn checksum() {
+        let clan = ClanTag("#2L99VLJ9P".to_string());
+        let build = || {
+            let mut storage = Storage::new_with_clans([clan.clone(), ClanTag("#9QQ".to_string())]);
+            let stats = storage.get_mut(&clan, &season()).unwrap();
+            for (tag, score) in [("#2PP", 100), ("#2QQ", 200), ("#2RR", 300)] {
+                stats
+                    .ingest_games(PlayerTag(tag.to_string()), score)
+                    .unwrap();
+            }
+            storage
+        };
+
+        let mut storage = build();
+        assert_eq!(build().checksum(), storage.checksum());
+
+        let previous = storage.checksum();
+        storage
+            .get_mut(&clan, &season())
+            .unwrap()
+            .ingest_games(PlayerTag("#2PP".to_string()), 150)
+            .unwrap();
+        assert_ne!(previous, storage.checksum());
+    }
+
+    #[test]
+    fn player_clan_lookup() {
+        let clan = ClanTag("#2L99VLJ9P".to_string());
+        let player = PlayerTag("#2PYLQ".to_string());
+
+        let mut storage = Storage::empty();
+        storage.register_clan(clan.clone());
+        storage
+            .get_mut(&clan, &season())
+            .unwrap()
+            .player_names
+            .insert(player.clone(), "Player".to_string());
+
+        assert_eq!(vec![clan], storage.player_clan(&player, &season()));
+        assert_eq!(
+            Vec::<ClanTag>::new(),
+            storage.player_clan(&PlayerTag("#9QGRJ".to_string()), &season())
+        );
+    }
+
+    #[test]
+    fn closed_season_rejects_ingest() {
+        let player = PlayerTag("#2PP".to_string());
+
+        let mut clan = ClanStorage::default();
+        clan.ingest_games(player.clone(), 100).unwrap();
+
+        clan.close();
+        assert_eq!(
+            Err(IngestError::SeasonClosed),
+            clan.ingest_games(player.clone(), 200)
+        );
+        assert_eq!(
+            Err(IngestError::SeasonClosed),
+            clan.ingest_names([(player.clone(), "Player".to_string())])
+        );
+        assert_eq!(100, clan.games.get(&player).unwrap().end_score);
+        assert!(clan.player_names.is_empty());
+
+        clan.reopen();
+        clan.ingest_games(player.clone(), 200).unwrap();
+        assert_eq!(200, clan.games.get(&player).unwrap().end_score);
+    }
+
+    #[test]
+    fn carry_forward_games() {
+        let clan = ClanTag("#2L99VLJ9P".to_string());
+        let player = PlayerTag("#2PP".to_string());
+        let next = Season {
+            year: 2023,
+            month: 9,
+        };
+
+        let mut storage = Storage::empty();
+        storage.register_clan(clan.clone());
+        let previous = storage.get_mut(&clan, &season()).unwrap();
+        previous.ingest_games(player.clone(), 1000).unwrap();
+        previous.ingest_games(player.clone(), 4000).unwrap();
+
+        storage
+            .carry_forward_games(&clan, &season(), &next)
+            .unwrap();
+
+        let games = storage
+            .get(&clan, &next)
+            .unwrap()
+            .games
+            .get(&player)
+            .unwrap();
+        assert_eq!(Some(4000), games.start_score);
+        assert_eq!(4000, games.end_score);
+
+        assert_eq!(
+            Err(IngestError::UnknownClan),
+            storage.carry_forward_games(&ClanTag("#9QQ".to_string()), &season(), &next)
+        );
+    }
+
+    #[test]
+    fn merge_from() {
+        let first = PlayerTag("#2PP".to_string());
+        let second = PlayerTag("#2QQ".to_string());
+        let start_time = Time {
+            year: 2023,
+            month: 8,
+            day: 4,
+        };
+        let raid = |looted| RaidWeekendStats {
+            start_time,
+            members: [(
+                first.clone(),
+                RaidMember {
+                    looted,
+                    attacks_used: 0,
+                    attacks_available: 0,
+                    attacks: Vec::new(),
+                },
+            )]
+            .into_iter()
+            .collect(),
+        };
+
+        let mut clan = ClanStorage::default();
+        clan.ingest_games(first.clone(), 100).unwrap();
+        clan.ingest_games(first.clone(), 300).unwrap();
+        clan.ingest_raid(raid(2000)).unwrap();
+        clan.player_names.insert(first.clone(), "First".to_string());
+
+        let mut other = ClanStorage::default();
+        other.ingest_games(first.clone(), 200).unwrap();
+        other.ingest_games(first.clone(), 500).unwrap();
+        other.ingest_games(second.clone(), 50).unwrap();
+        other.ingest_raid(raid(3000)).unwrap();
+        other
+            .ingest_war(WarStats {
+                attacks_per_member: 2,
+                opponent: None,
+                result: None,
+                start_time,
+                members: [(
+                    second.clone(),
+                    MemberWarStats {
+                        attacks: vec![attack("#8YY", 1, 3)],
+                    },
+                )]
+                .into_iter()
+                .collect(),
+            })
+            .unwrap();
+        other
+            .player_names
+            .insert(second.clone(), "Second".to_string());
+
+        clan.merge_from(&other);
+
+        let games = clan.games.get(&first).unwrap();
+        assert_eq!(Some(100), games.start_score);
+        assert_eq!(500, games.end_score);
+        assert!(clan.games.contains_key(&second));
+        assert_eq!(
+            3000,
+            clan.raid_weekend
+                .get(&start_time)
+                .unwrap()
+                .members
+                .get(&first)
+                .unwrap()
+                .looted
+        );
+        assert!(clan
+            .wars
+            .get(&start_time)
+            .unwrap()
+            .members
+            .contains_key(&second));
+        assert_eq!(2, clan.player_names.len());
+    }
+
+    #[test]
+    fn import_names() {
+        let mut clan = ClanStorage::default();
+
+        let invalid = clan
+            .import_names_csv("tag,name\n#2PP,First\n#2Q!,Second\n #2RR , Third \n2QQ,Fourth\n");
+
+        assert_eq!(vec![(3, "#2Q!,Second".to_string())], invalid);
+        assert_eq!(3, clan.player_names.len());
+        assert_eq!(
+            Some(&"First".to_string()),
+            clan.player_names.get(&PlayerTag("#2PP".to_string()))
+        );
+        assert_eq!(
+            Some(&"Third".to_string()),
+            clan.player_names.get(&PlayerTag("#2RR".to_string()))
+        );
+        assert_eq!(
+            Some(&"Fourth".to_string()),
+            clan.player_names.get(&PlayerTag("#2QQ".to_string()))
+        );
+    }
+
+    fn attack(target: &str, order: usize, stars: usize) -> WarAttack {
+        WarAttack {
+            destruction: stars * 33,
+            stars,
+            duration: 120,
+            target: Some(PlayerTag(target.to_string())),
+            order,
+            attacker_townhall: None,
+            defender_townhall: None,
+        }
+    }
+
+    #[test]
+    fn rename_player() {
+        let clan = ClanTag("#2L99VLJ9P".to_string());
+        let typo = PlayerTag("#2PQ".to_string());
+        let fixed = PlayerTag("#2PP".to_string());
+        let time = Time {
+            year: 2023,
+            month: 8,
+            day: 4,
+        };
+
+        let mut storage = Storage::new_with_clans([clan.clone()]);
+        let august = storage.get_mut(&clan, &season()).unwrap();
+        august
+            .ingest_names([
+                (typo.clone(), "Typo".to_string()),
+                (fixed.clone(), "Player".to_string()),
+            ])
+            .unwrap();
+        august
+            .ingest_cwl_round(
+                WarTag("#8QQ".to_string()),
+                0,
+                [(
+                    typo.clone(),
+                    MemberWarStats {
+                        attacks: vec![attack("#9LL", 1, 3)],
+                    },
+                )]
+                .into_iter()
+                .collect(),
+            )
+            .unwrap();
+        august
+            .ingest_war(WarStats {
+                start_time: time,
+                members: [(
+                    typo.clone(),
+                    MemberWarStats {
+                        attacks: vec![attack("#9LL", 1, 2)],
+                    },
+                )]
+                .into_iter()
+                .collect(),
+                attacks_per_member: 2,
+                opponent: None,
+                result: None,
+            })
+            .unwrap();
+        august.ingest_games(fixed.clone(), 500).unwrap();
+        august.ingest_games(typo.clone(), 100).unwrap();
+        august.ingest_games(typo.clone(), 1500).unwrap();
+
+        let previous = season().previous();
+        let july = storage.get_mut(&clan, &previous).unwrap();
+        july.ingest_raid(RaidWeekendStats {
+            start_time: time,
+            members: [
+                (
+                    typo.clone(),
+                    RaidMember {
+                        looted: 3000,
+                        attacks_used: 6,
+                        attacks_available: 6,
+                        attacks: Vec::new(),
+                    },
+                ),
+                (
+                    fixed.clone(),
+                    RaidMember {
+                        looted: 1000,
+                        attacks_used: 2,
+                        attacks_available: 6,
+                        attacks: Vec::new(),
+                    },
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        })
+        .unwrap();
+
+        storage.rename_player(&clan, &typo, &fixed).unwrap();
+
+        for (_, _, stats) in storage.iter() {
+            assert!(!stats.player_tags().contains(&typo));
+        }
+
+        let august = storage.get(&clan, &season()).unwrap();
+        assert_eq!(Some(&"Player".to_string()), august.player_names.get(&fixed));
+        assert_eq!(1, august.cwl.wars[0].members[&fixed].attacks.len());
+        assert_eq!(1, august.wars[&time].members[&fixed].attacks.len());
+        assert_eq!(Some(100), august.games[&fixed].start_score);
+        assert_eq!(1500, august.games[&fixed].end_score);
+
+        let july = storage.get(&clan, &previous).unwrap();
+        assert_eq!(3000, july.raid_weekend[&time].members[&fixed].looted);
+        assert_eq!(vec![clan.clone()], storage.player_clan(&fixed, &season()));
+        assert!(storage.player_clan(&typo, &season()).is_empty());
+
+        assert_eq!(
+            Err(IngestError::UnknownClan),
+            storage.rename_player(&ClanTag("#9QQ".to_string()), &typo, &fixed)
+        );
+    }
+
+    #[test]
+    fn star_consistency() {
+        let consistent = PlayerTag("#2PP".to_string());
+        let erratic = PlayerTag("#2QQ".to_string());
+
+        let mut clan = ClanStorage::default();
+        clan.ingest_war(WarStats {
+            start_time: Time {
+                year: 2023,
+                month: 8,
+                day: 4,
+            },
+            members: [
+                (
+                    consistent.clone(),
+                    MemberWarStats {
+                        attacks: vec![attack("#9LL", 1, 2), attack("#9QQ", 3, 2)],
+                    },
+                ),
+                (
+                    erratic.clone(),
+                    MemberWarStats {
+                        attacks: vec![attack("#9RR", 2, 0), attack("#9YY", 4, 3)],
+                    },
+                ),
+            ]
+            .into_iter()
+            .collect(),
+            attacks_per_member: 2,
+            opponent: None,
+            result: None,
+        })
+        .unwrap();
+        clan.ingest_cwl_round(
+            WarTag("#8QQ".to_string()),
+            0,
+            [
+                (
+                    consistent.clone(),
+                    MemberWarStats {
+                        attacks: vec![attack("#9LL", 1, 2)],
+                    },
+                ),
+                (
+                    erratic.clone(),
+                    MemberWarStats {
+                        attacks: vec![attack("#9QQ", 2, 3)],
+                    },
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        )
+        .unwrap();
+
+        assert_eq!(Some(0.0), clan.star_consistency(&consistent));
+        let erratic_consistency = clan.star_consistency(&erratic).unwrap();
+        assert!((erratic_consistency - 2.0f64.sqrt()).abs() < 1e-9);
+        assert_eq!(None, clan.star_consistency(&PlayerTag("#2RR".to_string())));
+    }
+
+    #[test]
+    fn war_history() {
+        let war = |day, opponent: &str, result, attacks: Vec<WarAttack>| WarStats {
+            start_time: Time {
+                year: 2023,
+                month: 8,
+                day,
+            },
+            members: [
+                (PlayerTag("#2PP".to_string()), MemberWarStats { attacks }),
+                (
+                    PlayerTag("#2QQ".to_string()),
+                    MemberWarStats {
+                        attacks: Vec::new(),
+                    },
+                ),
+            ]
+            .into_iter()
+            .collect(),
+            attacks_per_member: 2,
+            opponent: Some(ClanTag(opponent.to_string())),
+            result,
+        };
+
+        let mut clan = ClanStorage::default();
+        clan.ingest_war(war(
+            20,
+            "#9QQ",
+            None,
+            vec![attack("#9LL", 1, 2), attack("#9LL", 2, 3)],
+        ))
+        .unwrap();
+        clan.ingest_war(war(
+            4,
+            "#9LL",
+            Some(WarResult::Win),
+            vec![attack("#8YY", 1, 3), attack("#8QQ", 2, 2)],
+        ))
+        .unwrap();
+        clan.ingest_war(war(12, "#9RR", Some(WarResult::Loss), Vec::new()))
+            .unwrap();
+
+        let history = clan.war_history();
+        assert_eq!(
+            vec![
+                (4, Some(WarResult::Win), 5, 82.5),
+                (12, Some(WarResult::Loss), 0, 0.0),
+                (20, None, 3, 49.5),
+            ],
+            history
+                .iter()
+                .map(|row| (row.time.day, row.result, row.stars, row.destruction))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Some(ClanTag("#9LL".to_string())), history[0].opponent);
+    }
+
+    #[test]
+    fn war_record() {
+        let mut clan = ClanStorage::default();
+        assert_eq!((0, 0, 0), clan.war_record());
+
+        let results = [
+            Some(WarResult::Win),
+            Some(WarResult::Loss),
+            Some(WarResult::Win),
+            None,
+            Some(WarResult::Tie),
+        ];
+        for (day, result) in results.into_iter().enumerate() {
+            clan.ingest_war(WarStats {
+                start_time: Time {
+                    year: 2023,
+                    month: 8,
+                    day: day + 1,
+                },
+                members: HashMap::new(),
+                attacks_per_member: 2,
+                opponent: None,
+                result,
+            })
+            .unwrap();
+        }
+
+        assert_eq!((2, 1, 1), clan.war_record());
+
+        let legacy: WarResult = serde_json::from_str("\"Draw\"").unwrap();
+        assert_eq!(WarResult::Tie, legacy);
+    }
+
+    #[test]
+    fn effective_stars() {
+        let untargeted = WarAttack {
+            target: None,
+            ..attack("#9LL", 4, 1)
+        };
+        let stats = MemberWarStats {
+            attacks: vec![
+                attack("#9LL", 1, 2),
+                attack("#9LL", 2, 3),
+                attack("#9QQ", 3, 1),
+                untargeted,
+            ],
+        };
+        assert_eq!(5, stats.effective_stars());
+
+        let tag = PlayerTag("#2PP".to_string());
+        let mut clan = ClanStorage::default();
+        clan.ingest_war(WarStats {
+            start_time: Time {
+                year: 2023,
+                month: 8,
+                day: 4,
+            },
+            members: [(
+                tag.clone(),
+                MemberWarStats {
+                    attacks: vec![attack("#9LL", 1, 2), attack("#9LL", 2, 3)],
+                },
+            )]
+            .into_iter()
+            .collect(),
+            attacks_per_member: 2,
+            opponent: None,
+            result: None,
+        })
+        .unwrap();
+
+        let summaries: HashMap<_, _> = clan.players_summary().collect();
+        assert_eq!(3, summaries[&tag].war_stars);
+    }
+
+    #[test]
+    fn cwl_round_reimport() {
+        let player = PlayerTag("#2PP".to_string());
+        let members = |stars| {
+            [(
+                player.clone(),
+                MemberWarStats {
+                    attacks: vec![attack("#9LL", 1, stars)],
+                },
+            )]
+            .into_iter()
+            .collect::<HashMap<_, _>>()
+        };
+
+        let mut clan = ClanStorage::default();
+        clan.ingest_cwl_round(WarTag("#8QQ".to_string()), 1, members(2))
+            .unwrap();
+        clan.ingest_cwl_round(WarTag("#8YY".to_string()), 0, members(1))
+            .unwrap();
+        clan.ingest_cwl_round(WarTag("#8QQ".to_string()), 1, members(3))
+            .unwrap();
+
+        assert_eq!(2, clan.cwl.wars.len());
+        let sorted = clan.cwl.sorted_wars();
+        assert_eq!(
+            vec![(0, "#8YY", 1), (1, "#8QQ", 3)],
+            sorted
+                .iter()
+                .map(|war| (
+                    war.round,
+                    war.war_tag.as_ref().unwrap().0.as_str(),
+                    war.members[&player].attacks[0].stars
+                ))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn cwl_round_legacy() {
+        let mut clan: ClanStorage = serde_json::from_str(
+            r##"{
+                "cwl": {"wars": [{"members": {}}, {"members": {"#2PP": {"attacks": []}}}]},
+                "wars": {}, "games": {}, "raid_weekend": {}, "player_names": {}
+            }"##,
+        )
+        .unwrap();
+
+        clan.ingest_cwl_round(WarTag("#8QQ".to_string()), 1, HashMap::new())
+            .unwrap();
+
+        assert_eq!(2, clan.cwl.wars.len());
+        assert_eq!(
+            Some(&WarTag("#8QQ".to_string())),
+            clan.cwl.wars[1].war_tag.as_ref()
+        );
+        assert_eq!(1, clan.cwl.wars[1].round);
+    }
+
+    #[test]
+    fn attack_order() {
+        let player = PlayerTag("#2PP".to_string());
+        let members = || {
+            [(
+                player.clone(),
+                MemberWarStats {
+                    attacks: vec![attack("#9LL", 7, 3), attack("#9QQ", 2, 1)],
+                },
+            )]
+            .into_iter()
+            .collect::<HashMap<_, _>>()
+        };
+        let time = Time {
+            year: 2023,
+            month: 8,
+            day: 4,
+        };
+
+        let mut clan = ClanStorage::default();
+        clan.ingest_war(WarStats {
+            start_time: time,
+            members: members(),
+            attacks_per_member: 2,
+            opponent: None,
+            result: None,
+        })
+        .unwrap();
+        clan.ingest_cwl_round(WarTag("#8QQ".to_string()), 0, members())
+            .unwrap();
+
+        let order = |stats: &MemberWarStats| {
+            stats
+                .attacks
+                .iter()
+                .map(|attack| attack.order)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec![2, 7], order(&clan.wars[&time].members[&player]));
+        assert_eq!(vec![2, 7], order(&clan.cwl.wars[0].members[&player]));
+    }
+
+    #[test]
+    fn raid_participation() {
+        let raid = |attacks: &[(&str, usize)]| RaidWeekendStats {
+            start_time: Time {
+                year: 2023,
+                month: 8,
+                day: 4,
+            },
+            members: attacks
+                .iter()
+                .map(|(tag, used)| {
+                    (
+                        PlayerTag(tag.to_string()),
+                        RaidMember {
+                            looted: used * 1000,
+                            attacks_used: *used,
+                            attacks_available: 6,
+                            attacks: Vec::new(),
+                        },
+                    )
+                })
+                .collect(),
+        };
+
+        assert_eq!(1.0, raid(&[("#2PP", 6), ("#2QQ", 6)]).participation());
+        assert_eq!(0.5, raid(&[("#2PP", 6), ("#2QQ", 0)]).participation());
+        assert_eq!(0.5, raid(&[("#2PP", 3), ("#2QQ", 3)]).participation());
+        assert_eq!(0.0, raid(&[]).participation());
+    }
+
+    #[test]
+    fn merge() {
+        let first = ClanTag("#2PP".to_string());
+        let second = ClanTag("#2QQ".to_string());
+        let player = PlayerTag("#8YY".to_string());
+        let july = Season::from_ymd(2023, 7).unwrap();
+        let august = Season::from_ymd(2023, 8).unwrap();
+        let time = |day| Time {
+            year: 2023,
+            month: 8,
+            day,
+        };
+        let war = |day, attacks| WarStats {
+            start_time: time(day),
+            members: [(player.clone(), MemberWarStats { attacks })]
+                .into_iter()
+                .collect(),
+            attacks_per_member: 2,
+            opponent: None,
+            result: None,
+        };
+
+        let mut storage = Storage::new_with_clans([first.clone()]);
+        storage.get_mut(&first, &july).unwrap();
+        let stats = storage.get_mut(&first, &august).unwrap();
+        stats
+            .ingest_war(war(4, vec![attack("#9LL", 1, 2)]))
+            .unwrap();
+        stats.touch(time(5));
+
+        let mut other = Storage::new_with_clans([first.clone(), second.clone()]);
+        other
+            .get_mut(&second, &august)
+            .unwrap()
+            .player_names
+            .insert(player.clone(), "Other".to_string());
+        let stats = other.get_mut(&first, &august).unwrap();
+        stats
+            .ingest_war(war(4, vec![attack("#9LL", 1, 2), attack("#9QQ", 2, 3)]))
+            .unwrap();
+        stats.ingest_war(war(11, Vec::new())).unwrap();
+        stats.touch(time(12));
+
+        storage.revision = 2;
+        other.revision = 5;
+        storage.merge(other);
+        assert_eq!(5, storage.revision());
+
+        assert_eq!(vec![first.clone(), second.clone()], storage.list_clans());
+        assert_eq!(vec![july, august.clone()], storage.list_seasons(&first));
+        assert!(storage.get(&second, &august).is_some());
+
+        let merged = storage.get(&first, &august).unwrap();
+        assert_eq!(2, merged.wars.len());
+        assert_eq!(2, merged.wars[&time(4)].members[&player].attacks.len());
+        assert_eq!(Some(time(12)), merged.last_updated);
+        assert_eq!(vec![first, second], storage.player_clan(&player, &august));
+    }
+
+    #[test]
+    fn net_new_stars_same_base() {
+        let first = PlayerTag("#2PP".to_string());
+        let second = PlayerTag("#2QQ".to_string());
+
+        let war = WarStats {
+            attacks_per_member: 2,
+            opponent: None,
+            result: None,
+            start_time: Time {
+                year: 2023,
+                month: 8,
+                day: 4,
+            },
+            members: [
+                (
+                    first.clone(),
+                    MemberWarStats {
+                        attacks: vec![attack("#8YY", 1, 2)],
+                    },
+                ),
+                (
+                    second.clone(),
+                    MemberWarStats {
+                        attacks: vec![attack("#8YY", 2, 3), attack("#9LL", 3, 1)],
+                    },
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        };
+
+        let stars = war.net_new_stars();
+        assert_eq!(Some(&2), stars.get(&first));
+        assert_eq!(Some(&2), stars.get(&second));
+    }
+
+    #[test]
+    fn attacks_by_matchup() {
+        let matchup = |attacker, defender, stars| WarAttack {
+            attacker_townhall: Some(attacker),
+            defender_townhall: Some(defender),
+            ..attack("#8YY", 1, stars)
+        };
+
+        let mut clan = ClanStorage::default();
+        clan.ingest_war(WarStats {
+            attacks_per_member: 2,
+            opponent: None,
+            result: None,
+            start_time: Time {
+                year: 2023,
+                month: 8,
+                day: 4,
+            },
+            members: [
+                (
+                    PlayerTag("#2PP".to_string()),
+                    MemberWarStats {
+                        attacks: vec![matchup(15, 15, 3), matchup(15, 14, 3)],
+                    },
+                ),
+                (
+                    PlayerTag("#2QQ".to_string()),
+                    MemberWarStats {
+                        attacks: vec![matchup(15, 15, 1), attack("#9LL", 2, 2)],
+                    },
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        })
+        .unwrap();
+
+        let matchups = clan.attacks_by_matchup();
+        assert_eq!(2, matchups.len());
+        assert_eq!(
+            Some(&StarStats {
+                attacks: 2,
+                average_stars: 2.0,
+                average_destruction: 66.0,
+            }),
+            matchups.get(&(15, 15))
+        );
+        assert_eq!(
+            Some(&StarStats {
+                attacks: 1,
+                average_stars: 3.0,
+                average_destruction: 99.0,
+            }),
+            matchups.get(&(15, 14))
+        );
+    }
+
+    #[test]
+    fn missed_attacks_single_attack_war() {
+        let war = WarStats {
+            start_time: Time {
+                year: 2023,
+                month: 8,
+                day: 4,
+            },
+            attacks_per_member: 1,
+            opponent: None,
+            result: None,
+            members: [
+                (
+                    PlayerTag("#2PP".to_string()),
+                    MemberWarStats {
+                        attacks: vec![attack("#8YY", 1, 3)],
+                    },
+                ),
+                (
+                    PlayerTag("#2QQ".to_string()),
+                    MemberWarStats {
+                        attacks: Vec::new(),
+                    },
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        };
+
+        assert_eq!(
+            vec![(PlayerTag("#2QQ".to_string()), 1)],
+            war.missed_attacks()
+        );
+
+        let legacy: WarStats =
+            serde_json::from_str("{ \"start_time\": \"20230804T000000.000Z\", \"members\": {} }")
+                .unwrap();
+        assert_eq!(2, legacy.attacks_per_member);
+    }
+
+    #[test]
+    fn missed_attackers() {
+        let members: HashMap<_, _> = [
+            ("#2RR", vec![attack("#8YY", 1, 3), attack("#8QQ", 2, 2)]),
+            ("#2QQ", Vec::new()),
+            ("#2PP", vec![attack("#8YY", 3, 1)]),
+        ]
+        .into_iter()
+        .map(|(tag, attacks)| (PlayerTag(tag.to_string()), MemberWarStats { attacks }))
+        .collect();
+
+        let war = WarStats {
+            start_time: Time {
+                year: 2023,
+                month: 8,
+                day: 4,
+            },
+            attacks_per_member: 2,
+            opponent: None,
+            result: None,
+            members: members.clone(),
+        };
+        assert_eq!(
+            vec![PlayerTag("#2PP".to_string()), PlayerTag("#2QQ".to_string())],
+            war.missed_attackers(2)
+        );
+        assert_eq!(vec![PlayerTag("#2QQ".to_string())], war.missed_attackers(1));
+
+        let cwl = CwlWarStats {
+            members,
+            ..Default::default()
+        };
+        assert_eq!(vec![PlayerTag("#2QQ".to_string())], cwl.missed_attackers());
+    }
+
+    #[test]
+    fn fastest_three_stars() {
+        let fast = PlayerTag("#2PP".to_string());
+        let slow = PlayerTag("#2QQ".to_string());
+        let start_time = Time {
+            year: 2023,
+            month: 8,
+            day: 4,
+        };
+
+        let timed = |stars, duration| WarAttack {
+            duration,
+            ..attack("#8YY", 1, stars)
+        };
+
+        let mut clan = ClanStorage::default();
+        clan.ingest_war(WarStats {
+            attacks_per_member: 2,
+            opponent: None,
+            result: None,
+            start_time,
+            members: [
+                (
+                    fast.clone(),
+                    MemberWarStats {
+                        attacks: vec![timed(3, 95), timed(3, 80)],
+                    },
+                ),
+                (
+                    slow.clone(),
+                    MemberWarStats {
+                        attacks: vec![timed(2, 30), timed(3, 150)],
+                    },
+                ),
+                (
+                    PlayerTag("#2RR".to_string()),
+                    MemberWarStats {
+                        attacks: vec![timed(2, 20)],
+                    },
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        })
+        .unwrap();
+
+        assert_eq!(
+            vec![(fast.clone(), 80), (slow, 150)],
+            clan.fastest_three_stars(10)
+        );
+        assert_eq!(vec![(fast, 80)], clan.fastest_three_stars(1));
+    }
+}
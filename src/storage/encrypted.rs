@@ -0,0 +1,131 @@
+use std::{future::Future, pin::Pin};
+
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Key, Nonce,
+};
+
+use crate::{StorageBackend, StorageError};
+
+/// The Prefix of all the Content written by [`EncryptedStorage`], the last Byte is the Version
+/// of the Format and is increased whenever the Algorithm changes
+const MAGIC: [u8; 4] = *b"GPE\x01";
+
+/// The Length of the ChaCha20-Poly1305 Nonce, which is stored right after the [`MAGIC`]
+const NONCE_LEN: usize = 12;
+
+pub(crate) fn encrypt(key: &[u8; 32], content: &[u8]) -> Result<Vec<u8>, StorageError> {
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let ciphertext = cipher
+        .encrypt(&nonce, content)
+        .map_err(|_| StorageError::Encryption("Encrypting the Content"))?;
+
+    let mut result = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+    result.extend_from_slice(&MAGIC);
+    result.extend_from_slice(&nonce);
+    result.extend_from_slice(&ciphertext);
+    Ok(result)
+}
+
+pub(crate) fn decrypt(key: &[u8; 32], content: &[u8]) -> Result<Vec<u8>, StorageError> {
+    let rest = content
+        .strip_prefix(&MAGIC)
+        .ok_or(StorageError::Encryption(
+            "Content is not encrypted or uses an unknown Format",
+        ))?;
+    if rest.len() < NONCE_LEN {
+        return Err(StorageError::Encryption("Content is truncated"));
+    }
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| StorageError::Encryption("Wrong Key or corrupted Content"))
+}
+
+/// Wraps another Backend, encrypting everything written to it with ChaCha20-Poly1305 using
+/// the given Key. Every write uses a new random Nonce, which is stored with the Content
+pub struct EncryptedStorage<S> {
+    inner: S,
+    key: [u8; 32],
+}
+
+impl<S> EncryptedStorage<S> {
+    pub fn new(inner: S, key: [u8; 32]) -> Self {
+        Self { inner, key }
+    }
+}
+
+impl<S> StorageBackend for EncryptedStorage<S>
+where
+    S: StorageBackend,
+{
+    fn write(
+        &mut self,
+        content: Vec<u8>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), StorageError>> + Send + 'static>> {
+        match encrypt(&self.key, &content) {
+            Ok(encrypted) => self.inner.write(encrypted),
+            Err(e) => Box::pin(async move { Err(e) }),
+        }
+    }
+
+    fn load(
+        &mut self,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<u8>, StorageError>> + Send + 'static>> {
+        let load = self.inner.load();
+        let key = self.key;
+
+        Box::pin(async move {
+            let content = load.await?;
+            decrypt(&key, &content)
+        })
+    }
+
+    fn exists(
+        &mut self,
+    ) -> Pin<Box<dyn Future<Output = Result<bool, StorageError>> + Send + 'static>> {
+        self.inner.exists()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClanTag, MemoryStorage, Storage};
+
+    #[tokio::test]
+    async fn round_trip() {
+        let inner = MemoryStorage::new();
+        let mut backend = EncryptedStorage::new(inner.clone(), [7; 32]);
+
+        let mut storage = Storage::new_with_clans([ClanTag("#2PP".to_string())]);
+        storage.save(&mut backend).await.unwrap();
+
+        let stored = inner.content().unwrap();
+        assert!(stored.starts_with(&MAGIC));
+        assert!(!stored.windows(4).any(|window| window == b"#2PP"));
+
+        let loaded = Storage::load(&mut backend).await.unwrap();
+        assert_eq!(storage.checksum(), loaded.checksum());
+    }
+
+    #[tokio::test]
+    async fn wrong_key() {
+        let inner = MemoryStorage::new();
+        EncryptedStorage::new(inner.clone(), [7; 32])
+            .write(b"content".to_vec())
+            .await
+            .unwrap();
+
+        let result = EncryptedStorage::new(inner, [8; 32]).load().await;
+        assert!(matches!(result, Err(StorageError::Encryption(_))));
+
+        let plain = MemoryStorage::with_content(b"{}".to_vec());
+        let result = EncryptedStorage::new(plain, [7; 32]).load().await;
+        assert!(matches!(result, Err(StorageError::Encryption(_))));
+    }
+}
@@ -1,6 +1,11 @@
-use std::{path::PathBuf, pin::Pin};
+use std::{
+    path::{Path, PathBuf},
+    pin::Pin,
+};
 
-use crate::StorageBackend;
+use crate::{StorageBackend, StorageError};
+
+use super::gzip;
 
 pub struct FileStorage {
     path: PathBuf,
@@ -14,17 +19,40 @@ impl FileStorage {
         Self { path: path.into() }
     }
 
+    /// Whether the content should be gzip compressed, based on the `.gz` extension of the path
+    fn compressed(&self) -> bool {
+        self.path
+            .extension()
+            .map(|ext| ext == "gz")
+            .unwrap_or(false)
+    }
+
     pub async fn read(&mut self) -> Result<Vec<u8>, std::io::Error> {
         tokio::fs::read(&self.path).await
     }
 
     pub async fn write(&mut self, content: &[u8]) -> Result<(), std::io::Error> {
-        if !self.path.exists() {
-            tokio::fs::File::create(&self.path).await?;
-        }
+        write_atomic(&self.path, content).await
+    }
+}
+
+/// The sibling File the Content is written to before it replaces the actual File
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
 
-        tokio::fs::write(&self.path, content).await
+/// Writes the Content to a temporary File first and then renames it over the actual File, so
+/// an interrupted Write never leaves a partially written File behind
+async fn write_atomic(path: &Path, content: &[u8]) -> Result<(), std::io::Error> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        tokio::fs::create_dir_all(parent).await?;
     }
+
+    let temp = temp_path(path);
+    tokio::fs::write(&temp, content).await?;
+    tokio::fs::rename(&temp, path).await
 }
 
 impl StorageBackend for FileStorage {
@@ -32,31 +60,154 @@ impl StorageBackend for FileStorage {
     fn write(
         &mut self,
         content: Vec<u8>,
-    ) -> Pin<Box<dyn std::future::Future<Output = Result<(), ()>> + Send + 'static>> {
+    ) -> Pin<Box<dyn std::future::Future<Output = Result<(), StorageError>> + Send + 'static>> {
         let path = self.path.clone();
+        let compressed = self.compressed();
 
         Box::pin(async move {
             tracing::trace!("Storing to File");
 
-            if !path.exists() {
-                tokio::fs::File::create(&path).await.map_err(|e| {
-                    tracing::error!("Creating file {:?}", e);
-                    ()
-                })?;
-            }
+            let content = if compressed {
+                gzip::compress(&content).map_err(StorageError::Compression)?
+            } else {
+                content
+            };
 
-            tokio::fs::write(&path, &content).await.map_err(|e| {
-                tracing::error!("Writing File {:?}", e);
-                ()
-            })
+            write_atomic(&path, &content)
+                .await
+                .map_err(StorageError::Io)
         })
     }
 
     fn load(
         &mut self,
-    ) -> Pin<Box<dyn std::future::Future<Output = Result<Vec<u8>, ()>> + Send + 'static>> {
+    ) -> Pin<Box<dyn std::future::Future<Output = Result<Vec<u8>, StorageError>> + Send + 'static>>
+    {
         let path = self.path.clone();
+        let compressed = self.compressed();
+
+        Box::pin(async move {
+            let content = tokio::fs::read(&path).await.map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => StorageError::NotFound,
+                _ => StorageError::Io(e),
+            })?;
+
+            if !compressed {
+                return Ok(content);
+            }
+
+            if !gzip::is_gzip(&content) {
+                tracing::warn!("Loading uncompressed content from {:?}", path);
+                return Ok(content);
+            }
+
+            gzip::decompress(&content).map_err(StorageError::Compression)
+        })
+    }
+
+    fn exists(
+        &mut self,
+    ) -> Pin<Box<dyn std::future::Future<Output = Result<bool, StorageError>> + Send + 'static>>
+    {
+        let path = self.path.clone();
+
+        Box::pin(async move { tokio::fs::try_exists(&path).await.map_err(StorageError::Io) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn write_load() {
+        let path =
+            std::env::temp_dir().join(format!("gold-pass-files-{}.json", std::process::id()));
+        let mut storage = FileStorage::new(&path);
+
+        StorageBackend::write(&mut storage, b"content".to_vec())
+            .await
+            .unwrap();
+        let loaded = StorageBackend::load(&mut storage).await.unwrap();
+
+        tokio::fs::remove_file(&path).await.unwrap();
+
+        assert_eq!(b"content".to_vec(), loaded);
+    }
+
+    #[tokio::test]
+    async fn load_missing() {
+        let path = std::env::temp_dir().join(format!("gold-pass-missing-{}", std::process::id()));
+        let mut storage = FileStorage::new(&path);
+
+        assert!(matches!(
+            StorageBackend::load(&mut storage).await,
+            Err(StorageError::NotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn load_unreadable() {
+        // Reading a Directory fails even with all the Permissions
+        let path =
+            std::env::temp_dir().join(format!("gold-pass-unreadable-{}", std::process::id()));
+        tokio::fs::create_dir_all(&path).await.unwrap();
+        let mut storage = FileStorage::new(&path);
+
+        let result = StorageBackend::load(&mut storage).await;
+        tokio::fs::remove_dir(&path).await.unwrap();
+
+        assert!(matches!(result, Err(StorageError::Io(_))));
+    }
+
+    #[tokio::test]
+    async fn interrupted_write() {
+        let dir = std::env::temp_dir().join(format!("gold-pass-atomic-{}", std::process::id()));
+        let path = dir.join("nested").join("storage.json");
+        let mut storage = FileStorage::new(&path);
+
+        StorageBackend::write(&mut storage, b"valid".to_vec())
+            .await
+            .unwrap();
+        assert!(!temp_path(&path).exists());
+
+        // A Write that got interrupted only ever touches the temporary File
+        tokio::fs::write(temp_path(&path), b"{ partial")
+            .await
+            .unwrap();
+        let loaded = StorageBackend::load(&mut storage).await.unwrap();
+
+        StorageBackend::write(&mut storage, b"updated".to_vec())
+            .await
+            .unwrap();
+        let updated = StorageBackend::load(&mut storage).await.unwrap();
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+
+        assert_eq!(b"valid".to_vec(), loaded);
+        assert_eq!(b"updated".to_vec(), updated);
+    }
+
+    #[tokio::test]
+    async fn gzip_extension() {
+        let path =
+            std::env::temp_dir().join(format!("gold-pass-files-{}.json.gz", std::process::id()));
+        let mut storage = FileStorage::new(&path);
+
+        StorageBackend::write(&mut storage, b"content".to_vec())
+            .await
+            .unwrap();
+        let raw = tokio::fs::read(&path).await.unwrap();
+        let loaded = StorageBackend::load(&mut storage).await.unwrap();
+
+        // Files that were written before enabling compression should still load
+        tokio::fs::write(&path, b"plain").await.unwrap();
+        let plain = StorageBackend::load(&mut storage).await.unwrap();
+
+        tokio::fs::remove_file(&path).await.unwrap();
 
-        Box::pin(async move { tokio::fs::read(&path).await.map_err(|e| ()) })
+        assert!(gzip::is_gzip(&raw));
+        assert_eq!(b"content".to_vec(), loaded);
+        assert_eq!(b"plain".to_vec(), plain);
     }
 }
@@ -0,0 +1,98 @@
+use std::{
+    future::Future,
+    io::{Read, Write},
+    pin::Pin,
+};
+
+use crate::{StorageBackend, StorageError};
+
+/// The first two Bytes of every gzip Stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+pub(crate) fn is_gzip(content: &[u8]) -> bool {
+    content.starts_with(&GZIP_MAGIC)
+}
+
+pub(crate) fn compress(content: &[u8]) -> Result<Vec<u8>, std::io::Error> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(content)?;
+    encoder.finish()
+}
+
+pub(crate) fn decompress(content: &[u8]) -> Result<Vec<u8>, std::io::Error> {
+    let mut decoder = flate2::read::GzDecoder::new(content);
+    let mut result = Vec::new();
+    decoder.read_to_end(&mut result)?;
+    Ok(result)
+}
+
+/// Wraps another Backend, compressing everything written to it. Loading also accepts
+/// uncompressed Content, so existing plain Files keep working
+pub struct GzipStorage<S> {
+    inner: S,
+}
+
+impl<S> GzipStorage<S> {
+    pub fn new(inner: S) -> Self {
+        Self { inner }
+    }
+}
+
+impl<S> StorageBackend for GzipStorage<S>
+where
+    S: StorageBackend,
+{
+    fn write(
+        &mut self,
+        content: Vec<u8>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), StorageError>> + Send + 'static>> {
+        match compress(&content) {
+            Ok(compressed) => self.inner.write(compressed),
+            Err(e) => Box::pin(async move { Err(StorageError::Compression(e)) }),
+        }
+    }
+
+    fn load(
+        &mut self,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<u8>, StorageError>> + Send + 'static>> {
+        let load = self.inner.load();
+
+        Box::pin(async move {
+            let content = load.await?;
+            if !is_gzip(&content) {
+                return Ok(content);
+            }
+
+            decompress(&content).map_err(StorageError::Compression)
+        })
+    }
+
+    fn exists(
+        &mut self,
+    ) -> Pin<Box<dyn Future<Output = Result<bool, StorageError>> + Send + 'static>> {
+        self.inner.exists()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClanTag, MemoryStorage, Storage};
+
+    #[tokio::test]
+    async fn round_trip() {
+        let inner = MemoryStorage::new();
+        let mut backend = GzipStorage::new(inner.clone());
+
+        let mut storage = Storage::new_with_clans([ClanTag("#2PP".to_string())]);
+        storage.save(&mut backend).await.unwrap();
+        assert!(is_gzip(&inner.content().unwrap()));
+
+        let loaded = Storage::load(&mut backend).await.unwrap();
+        assert_eq!(storage.checksum(), loaded.checksum());
+
+        let plain = MemoryStorage::with_content(serde_json::to_vec(&storage).unwrap());
+        let loaded = Storage::load(&mut GzipStorage::new(plain)).await.unwrap();
+        assert_eq!(storage.checksum(), loaded.checksum());
+    }
+}
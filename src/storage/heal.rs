@@ -0,0 +1,224 @@
+use crate::{ClanStorage, ClanTag, PlayerTag, Season, Storage, CWL_MAX_STARS_PER_ROUND};
+
+use super::{StorageBackend, StorageError};
+
+/// A Fix applied to the stored Data by [`Storage::load_and_heal`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HealFix {
+    /// The Season did not contain any Stats and was removed
+    EmptySeasonDropped,
+    /// An Attack had more Stars than possible and was clamped to the maximum
+    StarsClamped { player: PlayerTag, stars: usize },
+    /// An Attack had more than 100% Destruction and was clamped to 100
+    DestructionClamped {
+        player: PlayerTag,
+        destruction: usize,
+    },
+    /// The CWL Round was an exact Copy of an earlier Round and was removed
+    DuplicateCwlRoundRemoved { round: usize },
+    /// The Player had Stats recorded but no Name, so the Tag is used as the Name
+    MissingNameAdded { player: PlayerTag },
+}
+
+impl ClanStorage {
+    /// Fixes the Issues in the Stats that can be detected and fixed cheaply
+    pub fn heal(&mut self) -> Vec<HealFix> {
+        let mut fixes = Vec::new();
+
+        let attacks = self
+            .cwl
+            .wars
+            .iter_mut()
+            .flat_map(|war| war.members.iter_mut())
+            .chain(
+                self.wars
+                    .values_mut()
+                    .flat_map(|war| war.members.iter_mut()),
+            )
+            .flat_map(|(player, stats)| stats.attacks.iter_mut().map(move |a| (player, a)));
+        for (player, attack) in attacks {
+            if attack.stars > CWL_MAX_STARS_PER_ROUND {
+                fixes.push(HealFix::StarsClamped {
+                    player: player.clone(),
+                    stars: attack.stars,
+                });
+                attack.stars = CWL_MAX_STARS_PER_ROUND;
+            }
+            if attack.destruction > 100 {
+                fixes.push(HealFix::DestructionClamped {
+                    player: player.clone(),
+                    destruction: attack.destruction,
+                });
+                attack.destruction = 100;
+            }
+        }
+
+        // Empty Rounds are only placeholders for Rounds that have not been collected yet, so
+        // they are never considered Duplicates
+        let mut kept = Vec::with_capacity(self.cwl.wars.len());
+        for (round, war) in std::mem::take(&mut self.cwl.wars).into_iter().enumerate() {
+            if !war.members.is_empty() && kept.contains(&war) {
+                fixes.push(HealFix::DuplicateCwlRoundRemoved { round });
+            } else {
+                kept.push(war);
+            }
+        }
+        self.cwl.wars = kept;
+
+        let mut missing: Vec<PlayerTag> = self
+            .player_tags()
+            .into_iter()
+            .filter(|tag| !self.player_names.contains_key(tag))
+            .collect();
+        missing.sort_by(|a, b| a.0.cmp(&b.0));
+        for player in missing {
+            self.player_names.insert(player.clone(), player.0.clone());
+            fixes.push(HealFix::MissingNameAdded { player });
+        }
+
+        fixes
+    }
+}
+
+impl Storage {
+    /// Fixes the Issues that can be detected and fixed cheaply in every Clan and Season
+    pub fn heal(&mut self) -> Vec<(ClanTag, Season, HealFix)> {
+        let mut fixes = Vec::new();
+
+        for (clan, seasons) in self.clans.iter_mut() {
+            seasons.retain(|season, stats| {
+                if !stats.is_empty() {
+                    return true;
+                }
+
+                fixes.push((clan.clone(), season.clone(), HealFix::EmptySeasonDropped));
+                false
+            });
+        }
+
+        for (clan, season, stats) in self.iter_mut() {
+            fixes.extend(
+                stats
+                    .heal()
+                    .into_iter()
+                    .map(|fix| (clan.clone(), season.clone(), fix)),
+            );
+        }
+
+        fixes
+    }
+
+    /// Loads the Storage like [`Storage::load`] and heals it afterwards, returning all the
+    /// Fixes that were applied
+    pub async fn load_and_heal(
+        store: &mut dyn StorageBackend,
+    ) -> Result<(Self, Vec<(ClanTag, Season, HealFix)>), StorageError> {
+        let mut storage = Self::load(store).await?;
+        let fixes = storage.heal();
+
+        for (clan, season, fix) in fixes.iter() {
+            tracing::info!("Healed {:?} in {:?}: {:?}", clan, season, fix);
+        }
+
+        Ok((storage, fixes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryStorage;
+
+    #[tokio::test]
+    async fn load_and_heal() {
+        let fixture = serde_json::json!({
+            "clans": {
+                "#2PP": {
+                    "2023-07": {
+                        "cwl": { "wars": [] },
+                        "wars": {},
+                        "games": {},
+                        "raid_weekend": {},
+                        "player_names": {}
+                    },
+                    "2023-08": {
+                        "cwl": { "wars": [
+                            { "members": { "#2QQ": { "attacks": [
+                                { "destruction": 100, "stars": 3, "duration": 40 }
+                            ] } } },
+                            { "members": { "#2QQ": { "attacks": [
+                                { "destruction": 100, "stars": 3, "duration": 40 }
+                            ] } } },
+                            { "members": { "#2QQ": { "attacks": [
+                                { "destruction": 180, "stars": 5, "duration": 60 }
+                            ] } } }
+                        ] },
+                        "wars": {},
+                        "games": { "#2RR": { "start_score": 0, "end_score": 1000 } },
+                        "raid_weekend": {},
+                        "player_names": { "#2QQ": "Player" }
+                    }
+                }
+            }
+        });
+
+        let backend = MemoryStorage::with_content(serde_json::to_vec(&fixture).unwrap());
+        let mut backend: Box<dyn StorageBackend> = Box::new(backend);
+
+        let (storage, fixes) = Storage::load_and_heal(backend.as_mut()).await.unwrap();
+
+        let clan = ClanTag("#2PP".to_string());
+        let july = Season {
+            year: 2023,
+            month: 7,
+        };
+        let august = Season {
+            year: 2023,
+            month: 8,
+        };
+        let player = PlayerTag("#2QQ".to_string());
+        let fixes: Vec<HealFix> = fixes
+            .into_iter()
+            .map(|(c, season, fix)| {
+                assert_eq!(clan, c);
+                assert_eq!(
+                    if fix == HealFix::EmptySeasonDropped {
+                        &july
+                    } else {
+                        &august
+                    },
+                    &season
+                );
+                fix
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                HealFix::EmptySeasonDropped,
+                HealFix::StarsClamped {
+                    player: player.clone(),
+                    stars: 5
+                },
+                HealFix::DestructionClamped {
+                    player: player.clone(),
+                    destruction: 180
+                },
+                HealFix::DuplicateCwlRoundRemoved { round: 1 },
+                HealFix::MissingNameAdded {
+                    player: PlayerTag("#2RR".to_string())
+                },
+            ],
+            fixes
+        );
+
+        assert!(storage.get(&clan, &july).is_none());
+        let stats = storage.get(&clan, &august).unwrap();
+        assert_eq!(2, stats.cwl.wars.len());
+        let attack = &stats.cwl.wars[1].members[&player].attacks[0];
+        assert_eq!((3, 100), (attack.stars, attack.destruction));
+        assert_eq!(
+            Some(&"#2RR".to_string()),
+            stats.player_names.get(&PlayerTag("#2RR".to_string()))
+        );
+    }
+}
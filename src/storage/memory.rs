@@ -0,0 +1,79 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+use crate::{StorageBackend, StorageError};
+
+/// Keeps the last written Content in Memory, mostly useful for Tests. Clones share the same
+/// Content, so a Clone can be used to inspect what was written
+#[derive(Debug, Default, Clone)]
+pub struct MemoryStorage {
+    content: Arc<Mutex<Option<Vec<u8>>>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a Backend that already contains the given Content
+    pub fn with_content(content: Vec<u8>) -> Self {
+        Self {
+            content: Arc::new(Mutex::new(Some(content))),
+        }
+    }
+
+    /// The last written Content, if anything has been written yet
+    pub fn content(&self) -> Option<Vec<u8>> {
+        self.content.lock().unwrap().clone()
+    }
+}
+
+impl StorageBackend for MemoryStorage {
+    fn write(
+        &mut self,
+        content: Vec<u8>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), StorageError>> + Send + 'static>> {
+        *self.content.lock().unwrap() = Some(content);
+        Box::pin(async { Ok(()) })
+    }
+
+    fn load(
+        &mut self,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<u8>, StorageError>> + Send + 'static>> {
+        let content = self.content();
+        Box::pin(async move { content.ok_or(StorageError::NotFound) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClanTag, Storage};
+
+    #[tokio::test]
+    async fn round_trip() {
+        let mut backend = MemoryStorage::new();
+        assert!(matches!(
+            Storage::load(&mut backend).await,
+            Err(StorageError::NotFound)
+        ));
+
+        let mut storage = Storage::new_with_clans([ClanTag("#2PP".to_string())]);
+        storage.save(&mut backend).await.unwrap();
+
+        let loaded = Storage::load(&mut backend.clone()).await.unwrap();
+        assert_eq!(storage.checksum(), loaded.checksum());
+    }
+
+    #[tokio::test]
+    async fn with_content() {
+        let storage = Storage::new_with_clans([ClanTag("#2PP".to_string())]);
+        let mut backend = MemoryStorage::with_content(serde_json::to_vec(&storage).unwrap());
+
+        let loaded = Storage::load(&mut backend).await.unwrap();
+        assert_eq!(storage.checksum(), loaded.checksum());
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+/// Upgrades the raw serialized Data by one Version
+pub type Migration = fn(Value) -> Result<Value, MigrationError>;
+
+#[derive(Debug)]
+pub struct MigrationError {
+    /// The Version the failed Migration started from
+    pub from_version: u32,
+    pub reason: String,
+}
+
+impl MigrationError {
+    pub fn new<R>(from_version: u32, reason: R) -> Self
+    where
+        R: Into<String>,
+    {
+        Self {
+            from_version,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Migrating from Version {}: {}",
+            self.from_version, self.reason
+        )
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+/// The Registry of all Migrations, keyed by the Version they upgrade from
+pub struct Migrations {
+    steps: BTreeMap<u32, Migration>,
+}
+
+impl Migrations {
+    pub fn new() -> Self {
+        Self {
+            steps: BTreeMap::new(),
+        }
+    }
+
+    /// Registers the Migration from `from_version` to `from_version + 1`
+    pub fn register(mut self, from_version: u32, migration: Migration) -> Self {
+        self.steps.insert(from_version, migration);
+        self
+    }
+
+    /// The Version stored in the Data, Data without a Version is considered to be Version 0
+    pub fn version_of(data: &Value) -> u32 {
+        data.get("version")
+            .and_then(Value::as_u64)
+            .and_then(|v| u32::try_from(v).ok())
+            .unwrap_or(0)
+    }
+
+    /// Applies all the Migrations in sequence, starting at the Version of the Data.
+    ///
+    /// The Data is only borrowed, so if any Migration fails the original is left untouched and
+    /// nothing half-migrated can be persisted.
+    pub fn run(&self, data: &Value) -> Result<Value, MigrationError> {
+        let mut version = Self::version_of(data);
+        let mut current = data.clone();
+
+        while let Some(migration) = self.steps.get(&version) {
+            tracing::info!("Migrating Storage from Version {}", version);
+
+            current = migration(current).map_err(|e| {
+                tracing::error!("Migration from Version {} failed: {}", version, e);
+                e
+            })?;
+            version += 1;
+
+            match current.as_object_mut() {
+                Some(obj) => {
+                    obj.insert("version".to_string(), Value::from(version));
+                }
+                None => {
+                    return Err(MigrationError::new(
+                        version - 1,
+                        "Migration did not produce an Object",
+                    ));
+                }
+            }
+        }
+
+        tracing::info!("Storage is at Version {}", version);
+
+        Ok(current)
+    }
+}
+
+impl Default for Migrations {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add_field(mut data: Value) -> Result<Value, MigrationError> {
+        data["added"] = Value::from(true);
+        Ok(data)
+    }
+
+    fn rename_field(mut data: Value) -> Result<Value, MigrationError> {
+        let added = data
+            .as_object_mut()
+            .and_then(|obj| obj.remove("added"))
+            .ok_or_else(|| MigrationError::new(1, "Missing field"))?;
+        data["renamed"] = added;
+        Ok(data)
+    }
+
+    fn failing(_: Value) -> Result<Value, MigrationError> {
+        Err(MigrationError::new(1, "Always fails"))
+    }
+
+    #[test]
+    fn chained() {
+        let migrations = Migrations::new()
+            .register(0, add_field)
+            .register(1, rename_field);
+
+        let original = serde_json::json!({ "clans": {} });
+        let migrated = migrations.run(&original).unwrap();
+
+        assert_eq!(
+            serde_json::json!({ "clans": {}, "renamed": true, "version": 2 }),
+            migrated
+        );
+    }
+
+    #[test]
+    fn failing_leaves_original() {
+        let migrations = Migrations::new()
+            .register(0, add_field)
+            .register(1, failing);
+
+        let original = serde_json::json!({ "clans": {} });
+        let err = migrations.run(&original).unwrap_err();
+
+        assert_eq!(1, err.from_version);
+        assert_eq!(serde_json::json!({ "clans": {} }), original);
+        assert_eq!(0, Migrations::version_of(&original));
+    }
+}
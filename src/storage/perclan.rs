@@ -0,0 +1,211 @@
+use std::{collections::HashMap, future::Future, path::PathBuf, pin::Pin};
+
+use crate::{ClanStorage, ClanTag, Season, StorageError};
+
+type ClanFuture<T> = Pin<Box<dyn Future<Output = Result<T, StorageError>> + Send + 'static>>;
+
+/// The Seasons of a single Clan, as stored by a [`ClanBackend`]
+pub type ClanSeasons = HashMap<Season, ClanStorage>;
+
+/// A Backend that stores the Data of every Clan separately, so a single Clan can be loaded
+/// without loading all the others as well
+pub trait ClanBackend: Send {
+    /// All the Clans stored in the Backend
+    fn clans(&mut self) -> ClanFuture<Vec<ClanTag>>;
+    /// Loads the Content stored for the Clan, [`None`] if there is nothing stored for it
+    fn load_clan(&mut self, clan: &ClanTag) -> ClanFuture<Option<Vec<u8>>>;
+    fn write_clan(&mut self, clan: &ClanTag, content: Vec<u8>) -> ClanFuture<()>;
+}
+
+/// Stores every Clan in its own `<tag>.json` File in a Directory
+pub struct ClanDirectory {
+    path: PathBuf,
+}
+
+impl ClanDirectory {
+    pub fn new<P>(path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self { path: path.into() }
+    }
+
+    fn clan_path(&self, clan: &ClanTag) -> PathBuf {
+        let tag = clan.0.strip_prefix('#').unwrap_or(&clan.0);
+        self.path.join(format!("{}.json", tag))
+    }
+}
+
+impl ClanBackend for ClanDirectory {
+    fn clans(&mut self) -> ClanFuture<Vec<ClanTag>> {
+        let path = self.path.clone();
+
+        Box::pin(async move {
+            let mut entries = tokio::fs::read_dir(&path).await.map_err(StorageError::Io)?;
+
+            let mut clans = Vec::new();
+            while let Some(entry) = entries.next_entry().await.map_err(StorageError::Io)? {
+                let path = entry.path();
+                if path.extension().map(|ext| ext == "json").unwrap_or(false) {
+                    if let Some(tag) = path.file_stem().and_then(|s| s.to_str()) {
+                        clans.push(ClanTag(format!("#{}", tag)));
+                    }
+                }
+            }
+
+            Ok(clans)
+        })
+    }
+
+    fn load_clan(&mut self, clan: &ClanTag) -> ClanFuture<Option<Vec<u8>>> {
+        let path = self.clan_path(clan);
+
+        Box::pin(async move {
+            match tokio::fs::read(&path).await {
+                Ok(content) => Ok(Some(content)),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(StorageError::Io(e)),
+            }
+        })
+    }
+
+    fn write_clan(&mut self, clan: &ClanTag, content: Vec<u8>) -> ClanFuture<()> {
+        let path = self.clan_path(clan);
+
+        Box::pin(async move {
+            tokio::fs::write(&path, &content)
+                .await
+                .map_err(StorageError::Io)
+        })
+    }
+}
+
+async fn load_seasons(
+    backend: &mut dyn ClanBackend,
+    clan: &ClanTag,
+) -> Result<ClanSeasons, StorageError> {
+    match backend.load_clan(clan).await? {
+        Some(content) => serde_json::from_slice(&content).map_err(|e| {
+            tracing::error!("Deserializing Clan {:?}: {:?}", clan, e);
+            StorageError::from(e)
+        }),
+        None => Ok(ClanSeasons::new()),
+    }
+}
+
+/// Merges the two Sources into the Target one Clan at a time using [`ClanStorage::merge_from`],
+/// so only the Data of a single Clan from each Source is in memory at once. Returns the Number
+/// of merged Clans
+pub async fn merge_streaming(
+    first: &mut dyn ClanBackend,
+    second: &mut dyn ClanBackend,
+    target: &mut dyn ClanBackend,
+) -> Result<usize, StorageError> {
+    let mut clans = first.clans().await?;
+    for clan in second.clans().await? {
+        if !clans.contains(&clan) {
+            clans.push(clan);
+        }
+    }
+
+    for clan in clans.iter() {
+        let mut merged = load_seasons(first, clan).await?;
+        for (season, stats) in load_seasons(second, clan).await? {
+            merged.entry(season).or_default().merge_from(&stats);
+        }
+
+        let content = serde_json::to_vec(&merged)?;
+        target.write_clan(clan, content).await?;
+    }
+
+    Ok(clans.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    use crate::PlayerTag;
+
+    /// Keeps every Clan in memory
+    #[derive(Default)]
+    struct MemoryClans {
+        clans: Arc<Mutex<HashMap<ClanTag, Vec<u8>>>>,
+    }
+
+    impl ClanBackend for MemoryClans {
+        fn clans(&mut self) -> ClanFuture<Vec<ClanTag>> {
+            let clans = self.clans.lock().unwrap().keys().cloned().collect();
+            Box::pin(async move { Ok(clans) })
+        }
+
+        fn load_clan(&mut self, clan: &ClanTag) -> ClanFuture<Option<Vec<u8>>> {
+            let content = self.clans.lock().unwrap().get(clan).cloned();
+            Box::pin(async move { Ok(content) })
+        }
+
+        fn write_clan(&mut self, clan: &ClanTag, content: Vec<u8>) -> ClanFuture<()> {
+            self.clans.lock().unwrap().insert(clan.clone(), content);
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    fn source(clans: &[(&str, &str, usize)]) -> MemoryClans {
+        let backend = MemoryClans::default();
+        let season = Season::from_ymd(2023, 8).unwrap();
+
+        for (clan, player, score) in clans {
+            let mut stats = ClanStorage::default();
+            stats
+                .ingest_games(PlayerTag(player.to_string()), *score)
+                .unwrap();
+
+            let seasons: ClanSeasons = [(season.clone(), stats)].into_iter().collect();
+            backend.clans.lock().unwrap().insert(
+                ClanTag(clan.to_string()),
+                serde_json::to_vec(&seasons).unwrap(),
+            );
+        }
+
+        backend
+    }
+
+    #[tokio::test]
+    async fn merge_two_sources() {
+        let mut first = source(&[("#2PP", "#8YY", 100), ("#2QQ", "#8YY", 200)]);
+        let mut second = source(&[("#2QQ", "#9LL", 300), ("#2RR", "#9LL", 400)]);
+        let mut target = MemoryClans::default();
+
+        assert_eq!(
+            3,
+            merge_streaming(&mut first, &mut second, &mut target)
+                .await
+                .unwrap()
+        );
+
+        let season = Season::from_ymd(2023, 8).unwrap();
+        let players = |clan: &str| {
+            let content = target
+                .clans
+                .lock()
+                .unwrap()
+                .get(&ClanTag(clan.to_string()))
+                .cloned()
+                .unwrap();
+            let seasons: ClanSeasons = serde_json::from_slice(&content).unwrap();
+            let mut players: Vec<String> = seasons[&season]
+                .games
+                .keys()
+                .map(|tag| tag.0.clone())
+                .collect();
+            players.sort();
+            players
+        };
+
+        assert_eq!(vec!["#8YY"], players("#2PP"));
+        assert_eq!(vec!["#8YY", "#9LL"], players("#2QQ"));
+        assert_eq!(vec!["#9LL"], players("#2RR"));
+    }
+}
@@ -1,13 +1,50 @@
-use crate::StorageBackend;
+use std::sync::Arc;
 
+use crate::{Storage, StorageBackend, StorageError, Time};
+
+type Replica = Arc<tokio::sync::Mutex<Box<dyn StorageBackend>>>;
+
+/// Stores the Content in two Backends.
+///
+/// Writes and Loads succeed as long as at least one of the Replicas responds. When loading, the
+/// Content of both Replicas is compared and if they disagree, the Content with the higher
+/// Storage Revision wins, followed by the newest `last_updated` of any Clan and finally the
+/// primary. The winning Content is written back to the stale Replica, including one that is
+/// missing the Content entirely (read repair).
 pub struct Replicated {
-    primary: Box<dyn StorageBackend>,
-    secondary: Box<dyn StorageBackend>,
+    primary: Replica,
+    secondary: Replica,
 }
 
 impl Replicated {
     pub fn new(primary: Box<dyn StorageBackend>, secondary: Box<dyn StorageBackend>) -> Self {
-        Self { primary, secondary }
+        Self {
+            primary: Arc::new(tokio::sync::Mutex::new(primary)),
+            secondary: Arc::new(tokio::sync::Mutex::new(secondary)),
+        }
+    }
+}
+
+/// The Revision of the serialized Storage and the newest `last_updated` of any of its Clans,
+/// used to pick the newer of two Replicas. Content that can not be parsed is the oldest
+fn freshness(content: &[u8]) -> Option<(u64, Option<Time>)> {
+    let storage = Storage::load_lenient(content).ok()?;
+    let last_updated = storage
+        .iter()
+        .filter_map(|(_, _, stats)| stats.last_updated)
+        .max();
+
+    Some((storage.revision(), last_updated))
+}
+
+/// Writes the Content back to a stale Replica, which only gets logged if it fails as the
+/// Content itself was loaded fine
+async fn repair(replica: &Replica, content: Vec<u8>) {
+    tracing::info!("Repairing stale Replica");
+
+    let write = replica.lock().await.write(content);
+    if let Err(e) = write.await {
+        tracing::error!("Repairing Replica {:?}", e);
     }
 }
 
@@ -16,33 +53,171 @@ impl StorageBackend for Replicated {
     fn write(
         &mut self,
         content: Vec<u8>,
-    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), ()>> + Send + 'static>> {
-        let pfut = self.primary.write(content.clone());
-        let sfut = self.secondary.write(content);
+    ) -> std::pin::Pin<
+        Box<dyn std::future::Future<Output = Result<(), StorageError>> + Send + 'static>,
+    > {
+        let primary = self.primary.clone();
+        let secondary = self.secondary.clone();
 
         Box::pin(async move {
             tracing::trace!("Storing Replicated");
 
+            let pfut = primary.lock().await.write(content.clone());
+            let sfut = secondary.lock().await.write(content);
+
             let pres = pfut.await;
             let sres = sfut.await;
 
-            pres.ok().or(sres.ok()).ok_or(())
+            pres.or(sres)
         })
     }
 
     fn load(
         &mut self,
-    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Vec<u8>, ()>> + Send + 'static>>
-    {
-        let pfut = self.primary.load();
-        let sfut = self.secondary.load();
+    ) -> std::pin::Pin<
+        Box<dyn std::future::Future<Output = Result<Vec<u8>, StorageError>> + Send + 'static>,
+    > {
+        let primary = self.primary.clone();
+        let secondary = self.secondary.clone();
 
         Box::pin(async move {
-            if let Ok(r) = pfut.await {
-                return Ok(r);
+            let pfut = primary.lock().await.load();
+            let sfut = secondary.lock().await.load();
+
+            match (pfut.await, sfut.await) {
+                (Ok(p), Ok(s)) if p == s => Ok(p),
+                (Ok(p), Ok(s)) => match freshness(&p).cmp(&freshness(&s)) {
+                    std::cmp::Ordering::Less => {
+                        repair(&primary, s.clone()).await;
+                        Ok(s)
+                    }
+                    ordering => {
+                        if ordering == std::cmp::Ordering::Equal {
+                            tracing::warn!("Replicas differ but are equally new, keeping Primary");
+                        }
+                        repair(&secondary, p.clone()).await;
+                        Ok(p)
+                    }
+                },
+                (Ok(p), Err(StorageError::NotFound)) => {
+                    repair(&secondary, p.clone()).await;
+                    Ok(p)
+                }
+                (Err(StorageError::NotFound), Ok(s)) => {
+                    repair(&primary, s.clone()).await;
+                    Ok(s)
+                }
+                (Ok(p), Err(_)) => Ok(p),
+                (Err(_), Ok(s)) => Ok(s),
+                (Err(e), Err(_)) => Err(e),
+            }
+        })
+    }
+
+    fn exists(
+        &mut self,
+    ) -> std::pin::Pin<
+        Box<dyn std::future::Future<Output = Result<bool, StorageError>> + Send + 'static>,
+    > {
+        let primary = self.primary.clone();
+        let secondary = self.secondary.clone();
+
+        Box::pin(async move {
+            let pfut = primary.lock().await.exists();
+            if let Ok(true) = pfut.await {
+                return Ok(true);
             }
 
+            let sfut = secondary.lock().await.exists();
             sfut.await
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClanTag, MemoryStorage};
+
+    fn content(revision: u64, clan: &str) -> Vec<u8> {
+        updated_content(revision, clan, None)
+    }
+
+    fn updated_content(revision: u64, clan: &str, last_updated: Option<Time>) -> Vec<u8> {
+        let tag = ClanTag(clan.to_string());
+        let mut storage = crate::Storage::new_with_clans([tag.clone()]);
+        storage.revision = revision;
+        if let Some(time) = last_updated {
+            storage.get_mut(&tag, &time.into()).unwrap().touch(time);
+        }
+
+        storage.to_canonical_vec().unwrap()
+    }
+
+    #[tokio::test]
+    async fn newest_wins() {
+        let primary = MemoryStorage::with_content(content(3, "#2PP"));
+        let secondary = MemoryStorage::with_content(content(4, "#2QQ"));
+        let mut backend = Replicated::new(Box::new(primary.clone()), Box::new(secondary.clone()));
+
+        assert_eq!(content(4, "#2QQ"), backend.load().await.unwrap());
+        assert_eq!(Some(content(4, "#2QQ")), primary.content());
+        assert_eq!(Some(content(4, "#2QQ")), secondary.content());
+    }
+
+    #[tokio::test]
+    async fn same_revision() {
+        let time = |day| Time {
+            year: 2023,
+            month: 8,
+            day,
+        };
+        let older = updated_content(3, "#2PP", Some(time(4)));
+        let newer = updated_content(3, "#2QQ", Some(time(5)));
+        let primary = MemoryStorage::with_content(older);
+        let secondary = MemoryStorage::with_content(newer.clone());
+        let mut backend = Replicated::new(Box::new(primary.clone()), Box::new(secondary.clone()));
+
+        assert_eq!(newer, backend.load().await.unwrap());
+        assert_eq!(Some(newer), primary.content());
+
+        // Without anything to tell them apart, the Primary wins
+        let primary = MemoryStorage::with_content(content(3, "#2PP"));
+        let secondary = MemoryStorage::with_content(content(3, "#2QQ"));
+        let mut backend = Replicated::new(Box::new(primary.clone()), Box::new(secondary.clone()));
+
+        assert_eq!(content(3, "#2PP"), backend.load().await.unwrap());
+        assert_eq!(Some(content(3, "#2PP")), secondary.content());
+    }
+
+    #[tokio::test]
+    async fn failed_primary_write() {
+        let primary = MemoryStorage::new();
+        let secondary = MemoryStorage::new();
+        let mut backend = Replicated::new(Box::new(primary.clone()), Box::new(secondary.clone()));
+
+        let mut storage = crate::Storage::new_with_clans([ClanTag("#2PP".to_string())]);
+        storage.save(&mut backend).await.unwrap();
+
+        // Simulates the primary missing the second Write
+        let stale = primary.content().unwrap();
+        storage.register_clan(ClanTag("#2QQ".to_string()));
+        storage.save(&mut backend).await.unwrap();
+        let mut primary_backend = primary.clone();
+        primary_backend.write(stale).await.unwrap();
+
+        let loaded = crate::Storage::load(&mut backend).await.unwrap();
+        assert_eq!(storage.checksum(), loaded.checksum());
+        assert_eq!(secondary.content(), primary.content());
+    }
+
+    #[tokio::test]
+    async fn repair_missing() {
+        let primary = MemoryStorage::new();
+        let secondary = MemoryStorage::with_content(content(3, "#2PP"));
+        let mut backend = Replicated::new(Box::new(primary.clone()), Box::new(secondary.clone()));
+
+        assert_eq!(content(3, "#2PP"), backend.load().await.unwrap());
+        assert_eq!(Some(content(3, "#2PP")), primary.content());
+    }
+}
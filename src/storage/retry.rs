@@ -0,0 +1,178 @@
+use std::{future::Future, pin::Pin, sync::Arc, time::Duration};
+
+use crate::{StorageBackend, StorageError};
+
+/// Retries failed Writes and Loads of the inner Backend, doubling the Delay after every
+/// failed Attempt and returning the last Error once all the Attempts are used up. Only
+/// [transient](StorageError::is_transient) Errors are retried, all others are returned right
+/// away
+pub struct RetryStorage<S> {
+    inner: Arc<tokio::sync::Mutex<S>>,
+    max_attempts: usize,
+    base_delay: Duration,
+}
+
+impl<S> RetryStorage<S> {
+    pub fn new(inner: S, max_attempts: usize, base_delay: Duration) -> Self {
+        Self {
+            inner: Arc::new(tokio::sync::Mutex::new(inner)),
+            max_attempts,
+            base_delay,
+        }
+    }
+}
+
+/// Runs the Operation until it succeeds, fails with a permanent Error or the Attempts are used
+/// up
+async fn retry<S, T, F>(
+    inner: Arc<tokio::sync::Mutex<S>>,
+    max_attempts: usize,
+    base_delay: Duration,
+    mut op: F,
+) -> Result<T, StorageError>
+where
+    F: FnMut(&mut S) -> Pin<Box<dyn Future<Output = Result<T, StorageError>> + Send + 'static>>,
+{
+    let mut delay = base_delay;
+    let mut attempt = 1;
+    loop {
+        let fut = op(&mut *inner.lock().await);
+
+        match fut.await {
+            Ok(v) => return Ok(v),
+            Err(e) if !e.is_transient() || attempt >= max_attempts => return Err(e),
+            Err(e) => {
+                tracing::warn!("Attempt {} failed, retrying in {:?}: {}", attempt, delay, e);
+
+                tokio::time::sleep(delay).await;
+                delay = delay.saturating_mul(2);
+                attempt += 1;
+            }
+        }
+    }
+}
+
+impl<S> StorageBackend for RetryStorage<S>
+where
+    S: StorageBackend + 'static,
+{
+    fn write(
+        &mut self,
+        content: Vec<u8>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), StorageError>> + Send + 'static>> {
+        Box::pin(retry(
+            self.inner.clone(),
+            self.max_attempts,
+            self.base_delay,
+            move |inner| inner.write(content.clone()),
+        ))
+    }
+
+    fn load(
+        &mut self,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<u8>, StorageError>> + Send + 'static>> {
+        Box::pin(retry(
+            self.inner.clone(),
+            self.max_attempts,
+            self.base_delay,
+            |inner| inner.load(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryStorage;
+
+    /// Fails the given Number of Operations before passing them on
+    struct Flaky {
+        failures: usize,
+        attempts: usize,
+        inner: MemoryStorage,
+    }
+
+    impl Flaky {
+        fn attempt(&mut self) -> Result<(), StorageError> {
+            self.attempts += 1;
+            if self.attempts <= self.failures {
+                return Err(StorageError::Backend(format!("Attempt {}", self.attempts)));
+            }
+            Ok(())
+        }
+    }
+
+    impl StorageBackend for Flaky {
+        fn write(
+            &mut self,
+            content: Vec<u8>,
+        ) -> Pin<Box<dyn Future<Output = Result<(), StorageError>> + Send + 'static>> {
+            if let Err(e) = self.attempt() {
+                return Box::pin(async move { Err(e) });
+            }
+            self.inner.write(content)
+        }
+
+        fn load(
+            &mut self,
+        ) -> Pin<Box<dyn Future<Output = Result<Vec<u8>, StorageError>> + Send + 'static>> {
+            if let Err(e) = self.attempt() {
+                return Box::pin(async move { Err(e) });
+            }
+            self.inner.load()
+        }
+    }
+
+    #[tokio::test]
+    async fn third_attempt_wins() {
+        let inner = MemoryStorage::new();
+        let mut backend = RetryStorage::new(
+            Flaky {
+                failures: 2,
+                attempts: 0,
+                inner: inner.clone(),
+            },
+            3,
+            Duration::from_millis(1),
+        );
+
+        backend.write(b"content".to_vec()).await.unwrap();
+        assert_eq!(Some(b"content".to_vec()), inner.content());
+        assert_eq!(3, backend.inner.lock().await.attempts);
+    }
+
+    #[tokio::test]
+    async fn last_error() {
+        let mut backend = RetryStorage::new(
+            Flaky {
+                failures: 5,
+                attempts: 0,
+                inner: MemoryStorage::with_content(b"content".to_vec()),
+            },
+            3,
+            Duration::from_millis(1),
+        );
+
+        match backend.load().await {
+            Err(StorageError::Backend(e)) => assert_eq!("Attempt 3", e),
+            other => panic!("Unexpected {:?}", other),
+        }
+        assert_eq!(3, backend.inner.lock().await.attempts);
+    }
+
+    #[tokio::test]
+    async fn not_found_once() {
+        let mut backend = RetryStorage::new(
+            Flaky {
+                failures: 0,
+                attempts: 0,
+                inner: MemoryStorage::new(),
+            },
+            3,
+            Duration::from_secs(60),
+        );
+
+        assert!(matches!(backend.load().await, Err(StorageError::NotFound)));
+        assert_eq!(1, backend.inner.lock().await.attempts);
+    }
+}
@@ -1,17 +1,129 @@
-use crate::StorageBackend;
+use std::sync::{Arc, Mutex};
+
+use crate::{Clock, StorageBackend, StorageError, SystemClock};
+
+/// The Prefix of all the Keys used for the History of the stored Content
+const HISTORY_PREFIX: &str = "history/";
 
 pub struct S3Storage {
     bucket: s3::Bucket,
     filename: String,
+    content_type: String,
+    /// The ETag of the Content that was last loaded or written, which has to still match when
+    /// writing so we never overwrite Changes of other Instances
+    etag: Arc<Mutex<Option<String>>>,
+    /// Whether every Write also stores a timestamped Copy under [`HISTORY_PREFIX`]
+    history: bool,
+    clock: Arc<dyn Clock>,
 }
 
 impl S3Storage {
     pub fn new(bucket: s3::Bucket) -> Self {
+        Self::with_filename(bucket, "storage.json")
+    }
+
+    /// Stores the Content under the given Filename in the Bucket, so several Bots can share
+    /// the same Bucket
+    pub fn with_filename<F>(bucket: s3::Bucket, filename: F) -> Self
+    where
+        F: Into<String>,
+    {
         Self {
             bucket,
-            filename: "storage.json".to_string(),
+            filename: filename.into(),
+            content_type: "application/json".to_string(),
+            etag: Arc::new(Mutex::new(None)),
+            history: false,
+            clock: Arc::new(SystemClock),
         }
     }
+
+    /// The Content-Type the Content is uploaded with, `application/json` by default
+    pub fn content_type<C>(mut self, content_type: C) -> Self
+    where
+        C: Into<String>,
+    {
+        self.content_type = content_type.into();
+        self
+    }
+
+    /// Whether every successful Write should also store a timestamped Copy of the Content, so
+    /// it can be restored later using [`S3Storage::restore_from`]. Disabled by default
+    pub fn history(mut self, enabled: bool) -> Self {
+        self.history = enabled;
+        self
+    }
+
+    /// The Clock used for the Timestamps of the History
+    pub fn clock<C>(mut self, clock: C) -> Self
+    where
+        C: Clock + 'static,
+    {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// The Prefix of the History Keys for our Filename, `storage.json` uses `history/storage-`
+    fn history_prefix(&self) -> String {
+        let stem = self
+            .filename
+            .split_once('.')
+            .map(|(stem, _)| stem)
+            .unwrap_or(&self.filename);
+        format!("{}{}-", HISTORY_PREFIX, stem)
+    }
+
+    /// The History Key for a Write at the current Time, like
+    /// `history/storage-20240131-235959.json`
+    fn history_key(&self) -> String {
+        let extension = self
+            .filename
+            .split_once('.')
+            .map(|(_, ext)| format!(".{}", ext))
+            .unwrap_or_default();
+
+        format!(
+            "{}{}{}",
+            self.history_prefix(),
+            self.clock.now().format("%Y%m%d-%H%M%S"),
+            extension
+        )
+    }
+
+    /// All the Keys in the History of our File, oldest first
+    pub async fn list_history(&self) -> Result<Vec<String>, StorageError> {
+        let results = self
+            .bucket
+            .list(self.history_prefix(), None)
+            .await
+            .map_err(|e| StorageError::Backend(e.to_string()))?;
+
+        let mut keys: Vec<String> = results
+            .into_iter()
+            .flat_map(|result| result.contents)
+            .map(|object| object.key)
+            .collect();
+        keys.sort();
+
+        Ok(keys)
+    }
+
+    /// Replaces the stored Content with the Copy in the History under the given Key
+    pub async fn restore_from(&mut self, key: &str) -> Result<(), StorageError> {
+        let content = match self.bucket.get_object(key).await {
+            Ok(c) if c.status_code() == 404 => return Err(StorageError::NotFound),
+            Ok(c) if (200..300).contains(&c.status_code()) => c.to_vec(),
+            Ok(c) => {
+                return Err(StorageError::Backend(format!(
+                    "Unexpected Status {}",
+                    c.status_code()
+                )))
+            }
+            Err(e) => return Err(StorageError::Backend(e.to_string())),
+        };
+
+        StorageBackend::write(self, content).await
+    }
 }
 
 impl StorageBackend for S3Storage {
@@ -19,9 +131,14 @@ impl StorageBackend for S3Storage {
     fn write(
         &mut self,
         content: Vec<u8>,
-    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), ()>> + Send + 'static>> {
+    ) -> std::pin::Pin<
+        Box<dyn std::future::Future<Output = Result<(), StorageError>> + Send + 'static>,
+    > {
         let bucket = self.bucket.clone();
         let filename = self.filename.clone();
+        let content_type = self.content_type.clone();
+        let etag = self.etag.clone();
+        let history_key = self.history.then(|| self.history_key());
 
         Box::pin(async move {
             let filename = filename;
@@ -30,41 +147,344 @@ impl StorageBackend for S3Storage {
             tracing::trace!("Storing to S3 Bucket");
 
             if let Ok(previous) = bucket.get_object(filename.clone()).await {
+                let previous_etag = previous.headers().get("etag").cloned();
                 let previous_content = previous.to_vec();
 
                 if content == previous_content {
                     tracing::trace!("Skipping upload as content is the same");
+                    *etag.lock().unwrap() = previous_etag;
                     return Ok(());
                 }
             }
 
-            let res = bucket.put_object_with_content_type(&filename, &content, "application/json");
+            let expected = etag.lock().unwrap().clone();
+            let mut headers = http::HeaderMap::new();
+            match expected {
+                Some(expected) => {
+                    headers.insert(
+                        http::header::IF_MATCH,
+                        expected.parse().map_err(|_| {
+                            StorageError::Backend(format!("Invalid ETag {}", expected))
+                        })?,
+                    );
+                }
+                // Without a known ETag the File should not exist yet, so if it does someone else
+                // created it in the meantime
+                None => {
+                    headers.insert(
+                        http::header::IF_NONE_MATCH,
+                        http::HeaderValue::from_static("*"),
+                    );
+                }
+            }
+            let bucket = bucket.with_extra_headers(headers);
 
-            match res.await {
-                Ok(_) => {}
-                Err(e) => {
-                    tracing::error!("{:?}", e);
+            let res = bucket.put_object_with_content_type(&filename, &content, &content_type);
 
-                    return Err(());
+            match res.await {
+                Ok(response) if response.status_code() == 412 => {
+                    return Err(StorageError::Conflict)
+                }
+                Ok(response) if (200..300).contains(&response.status_code()) => {
+                    *etag.lock().unwrap() = response.headers().get("etag").cloned();
+                }
+                Ok(response) => {
+                    return Err(StorageError::Backend(format!(
+                        "Unexpected Status {}",
+                        response.status_code()
+                    )))
                 }
+                Err(e) => return Err(StorageError::Backend(e.to_string())),
             };
 
+            // The Content itself is already stored, so a missing History entry is not fatal
+            if let Some(history_key) = history_key {
+                // The Preconditions only apply to the main File
+                let bucket = bucket.with_extra_headers(http::HeaderMap::new());
+                match bucket
+                    .put_object_with_content_type(&history_key, &content, &content_type)
+                    .await
+                {
+                    Ok(response) if (200..300).contains(&response.status_code()) => {}
+                    Ok(response) => tracing::error!(
+                        "Storing History {:?}: Status {}",
+                        history_key,
+                        response.status_code()
+                    ),
+                    Err(e) => tracing::error!("Storing History {:?}: {:?}", history_key, e),
+                }
+            }
+
             Ok(())
         })
     }
 
     fn load(
         &mut self,
-    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Vec<u8>, ()>> + Send + 'static>>
-    {
+    ) -> std::pin::Pin<
+        Box<dyn std::future::Future<Output = Result<Vec<u8>, StorageError>> + Send + 'static>,
+    > {
         let bucket = self.bucket.clone();
         let filename = self.filename.clone();
+        let etag = self.etag.clone();
 
         Box::pin(async move {
             match bucket.get_object(filename).await {
-                Ok(c) => Ok(c.to_vec()),
-                Err(e) => Err(()),
+                Ok(c) if c.status_code() == 404 => {
+                    *etag.lock().unwrap() = None;
+                    Err(StorageError::NotFound)
+                }
+                Ok(c) if (200..300).contains(&c.status_code()) => {
+                    *etag.lock().unwrap() = c.headers().get("etag").cloned();
+                    Ok(c.to_vec())
+                }
+                Ok(c) => Err(StorageError::Backend(format!(
+                    "Unexpected Status {}",
+                    c.status_code()
+                ))),
+                Err(e) => Err(StorageError::Backend(e.to_string())),
+            }
+        })
+    }
+
+    fn exists(
+        &mut self,
+    ) -> std::pin::Pin<
+        Box<dyn std::future::Future<Output = Result<bool, StorageError>> + Send + 'static>,
+    > {
+        let bucket = self.bucket.clone();
+        let filename = self.filename.clone();
+
+        Box::pin(async move {
+            match bucket.head_object(filename).await {
+                Ok((_, 404)) => Ok(false),
+                Ok((_, status)) if (200..300).contains(&status) => Ok(true),
+                Ok((_, status)) => Err(StorageError::Backend(format!(
+                    "Unexpected Status {}",
+                    status
+                ))),
+                Err(e) => Err(StorageError::Backend(e.to_string())),
             }
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::FixedClock;
+
+    fn bucket() -> s3::Bucket {
+        s3::Bucket::new(
+            "bucket",
+            s3::Region::Custom {
+                region: "default".to_string(),
+                endpoint: "http://localhost:9000".to_string(),
+            },
+            s3::creds::Credentials::anonymous().unwrap(),
+        )
+        .unwrap()
+    }
+
+    /// The Content and ETag of every Object stored by the [`mock_s3`] Server, by their Key
+    type MockObjects = Arc<Mutex<HashMap<String, (Vec<u8>, u32)>>>;
+
+    /// Serves the Objects of a single Bucket, only accepting Writes whose `If-Match` Header
+    /// matches the current ETag and whose `If-None-Match: *` Header targets a missing Object
+    async fn mock_s3(objects: MockObjects) -> s3::Bucket {
+        use axum::{
+            body::Bytes,
+            extract::{Path, Query, State},
+            http::{HeaderMap, StatusCode},
+            routing::get,
+            Router,
+        };
+
+        async fn get_object(
+            State(objects): State<MockObjects>,
+            Path(key): Path<String>,
+        ) -> (StatusCode, HeaderMap, Vec<u8>) {
+            let mut headers = HeaderMap::new();
+            match objects.lock().unwrap().get(&key).cloned() {
+                Some((content, version)) => {
+                    headers.insert("etag", format!("\"{}\"", version).parse().unwrap());
+                    (StatusCode::OK, headers, content)
+                }
+                None => (StatusCode::NOT_FOUND, headers, Vec::new()),
+            }
+        }
+
+        async fn put_object(
+            State(objects): State<MockObjects>,
+            Path(key): Path<String>,
+            headers: HeaderMap,
+            body: Bytes,
+        ) -> (StatusCode, HeaderMap) {
+            let mut objects = objects.lock().unwrap();
+            let version = objects.get(&key).map(|(_, v)| *v).unwrap_or(0);
+            if let Some(expected) = headers.get("if-match") {
+                if expected.to_str().unwrap() != format!("\"{}\"", version) {
+                    return (StatusCode::PRECONDITION_FAILED, HeaderMap::new());
+                }
+            }
+            if headers.get("if-none-match").is_some() && objects.contains_key(&key) {
+                return (StatusCode::PRECONDITION_FAILED, HeaderMap::new());
+            }
+
+            objects.insert(key, (body.to_vec(), version + 1));
+            let mut headers = HeaderMap::new();
+            headers.insert("etag", format!("\"{}\"", version + 1).parse().unwrap());
+            (StatusCode::OK, headers)
+        }
+
+        async fn list_objects(
+            State(objects): State<MockObjects>,
+            Query(query): Query<HashMap<String, String>>,
+        ) -> String {
+            let prefix = query.get("prefix").cloned().unwrap_or_default();
+            let contents: String = objects
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(key, _)| key.starts_with(&prefix))
+                .map(|(key, (content, _))| {
+                    format!(
+                        "<Contents><Key>{}</Key><LastModified>2024-01-01T00:00:00.000Z</LastModified><Size>{}</Size></Contents>",
+                        key,
+                        content.len()
+                    )
+                })
+                .collect();
+
+            format!(
+                "<ListBucketResult><Name>bucket</Name><Prefix>{}</Prefix><IsTruncated>false</IsTruncated>{}</ListBucketResult>",
+                prefix, contents
+            )
+        }
+
+        let app = Router::new()
+            .route("/bucket/", get(list_objects))
+            .route("/bucket/*key", get(get_object).put(put_object))
+            .with_state(objects);
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+
+        s3::Bucket::new(
+            "bucket",
+            s3::Region::Custom {
+                region: "default".to_string(),
+                endpoint: format!("http://{}", addr),
+            },
+            s3::creds::Credentials::anonymous().unwrap(),
+        )
+        .unwrap()
+        .with_path_style()
+    }
+
+    #[tokio::test]
+    async fn conflicting_write() {
+        let objects: MockObjects = Default::default();
+        let set = |content: &[u8], version| {
+            objects
+                .lock()
+                .unwrap()
+                .insert("storage.json".to_string(), (content.to_vec(), version));
+        };
+        let get = || objects.lock().unwrap()["storage.json"].clone();
+
+        set(b"first", 1);
+        let mut storage = S3Storage::new(mock_s3(objects.clone()).await);
+
+        assert_eq!(b"first".to_vec(), storage.load().await.unwrap());
+
+        // Another Instance writes in the meantime
+        set(b"other", 2);
+        assert!(matches!(
+            storage.write(b"mine".to_vec()).await,
+            Err(StorageError::Conflict)
+        ));
+        assert_eq!(b"other".to_vec(), get().0);
+
+        assert_eq!(b"other".to_vec(), storage.load().await.unwrap());
+        storage.write(b"mine".to_vec()).await.unwrap();
+        assert_eq!((b"mine".to_vec(), 3), get());
+
+        // Our own Writes update the ETag, so writing again works without loading first
+        storage.write(b"again".to_vec()).await.unwrap();
+        assert_eq!(b"again".to_vec(), get().0);
+    }
+
+    #[tokio::test]
+    async fn conflicting_create() {
+        let objects: MockObjects = Default::default();
+        let mut first = S3Storage::new(mock_s3(objects.clone()).await);
+        let mut second = S3Storage::new(mock_s3(objects.clone()).await);
+
+        assert!(matches!(first.load().await, Err(StorageError::NotFound)));
+        assert!(matches!(second.load().await, Err(StorageError::NotFound)));
+
+        first.write(b"first".to_vec()).await.unwrap();
+        assert!(matches!(
+            second.write(b"second".to_vec()).await,
+            Err(StorageError::Conflict)
+        ));
+        assert_eq!(b"first".to_vec(), objects.lock().unwrap()["storage.json"].0);
+
+        assert_eq!(b"first".to_vec(), second.load().await.unwrap());
+        second.write(b"second".to_vec()).await.unwrap();
+        assert_eq!(
+            b"second".to_vec(),
+            objects.lock().unwrap()["storage.json"].0
+        );
+    }
+
+    #[tokio::test]
+    async fn history() {
+        let objects: MockObjects = Default::default();
+        let time = chrono::DateTime::parse_from_rfc3339("2024-01-31T23:59:58Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+
+        let mut storage = S3Storage::new(mock_s3(objects.clone()).await)
+            .history(true)
+            .clock(FixedClock(time));
+        storage.write(b"first".to_vec()).await.unwrap();
+
+        let key = "history/storage-20240131-235958.json";
+        assert_eq!(vec![key.to_string()], storage.list_history().await.unwrap());
+        assert_eq!(b"first".to_vec(), objects.lock().unwrap()[key].0);
+
+        storage = storage.clock(FixedClock(time + chrono::Duration::seconds(1)));
+        storage.write(b"second".to_vec()).await.unwrap();
+        assert_eq!(2, storage.list_history().await.unwrap().len());
+
+        storage.restore_from(key).await.unwrap();
+        assert_eq!(b"first".to_vec(), storage.load().await.unwrap());
+
+        let mut plain = S3Storage::with_filename(mock_s3(objects.clone()).await, "plain.json");
+        plain.write(b"plain".to_vec()).await.unwrap();
+        assert!(plain.list_history().await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn configure() {
+        let default = S3Storage::new(bucket());
+        assert_eq!("storage.json", default.filename);
+        assert_eq!("application/json", default.content_type);
+        assert!(!default.history);
+
+        let configured =
+            S3Storage::with_filename(bucket(), "other.json.gz").content_type("application/gzip");
+        assert_eq!("other.json.gz", configured.filename);
+        assert_eq!("application/gzip", configured.content_type);
+        assert_eq!("history/other-", configured.history_prefix());
+    }
+}
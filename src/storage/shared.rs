@@ -0,0 +1,119 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::{ClanTag, Storage};
+
+/// A cloneable Handle to a Storage, that allows Updates for different Clans to run concurrently.
+///
+/// Anyone updating a Clan should hold the Lock for that Clan for the entire Update and only
+/// access the Storage itself through short calls to [`SharedStorage::with_storage`].
+#[derive(Clone)]
+pub struct SharedStorage {
+    storage: Arc<Mutex<Storage>>,
+    clan_locks: Arc<Mutex<HashMap<ClanTag, Arc<tokio::sync::Mutex<()>>>>>,
+}
+
+pub type ClanGuard = tokio::sync::OwnedMutexGuard<()>;
+
+impl SharedStorage {
+    pub fn new(storage: Storage) -> Self {
+        Self {
+            storage: Arc::new(Mutex::new(storage)),
+            clan_locks: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn clan_lock(&self, clan: &ClanTag) -> Arc<tokio::sync::Mutex<()>> {
+        let mut locks = self.clan_locks.lock().unwrap();
+        locks.entry(clan.clone()).or_default().clone()
+    }
+
+    /// Waits until no one else is updating the Clan
+    pub async fn lock_clan(&self, clan: &ClanTag) -> ClanGuard {
+        self.clan_lock(clan).lock_owned().await
+    }
+
+    /// Attempts to lock the Clan, returning `None` if it is currently being updated
+    pub fn try_lock_clan(&self, clan: &ClanTag) -> Option<ClanGuard> {
+        self.clan_lock(clan).try_lock_owned().ok()
+    }
+
+    /// Runs the given closure with exclusive access to the Storage
+    pub fn with_storage<F, R>(&self, func: F) -> R
+    where
+        F: FnOnce(&mut Storage) -> R,
+    {
+        let mut storage = self.storage.lock().unwrap();
+        func(&mut storage)
+    }
+
+    /// A Copy of the current State of the Storage
+    pub fn snapshot(&self) -> Storage {
+        self.with_storage(|storage| storage.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{PlayerTag, Season};
+
+    #[tokio::test]
+    async fn different_clans_in_parallel() {
+        let first = ClanTag("#2L99VLJ9P".to_string());
+        let second = ClanTag("#9QQ".to_string());
+        let season = Season {
+            year: 2023,
+            month: 8,
+        };
+
+        let shared = SharedStorage::new(Storage::new_with_clans([first.clone(), second.clone()]));
+        let barrier = tokio::sync::Barrier::new(2);
+
+        let update = |clan: ClanTag| {
+            let shared = shared.clone();
+            let barrier = &barrier;
+            let season = season.clone();
+            async move {
+                let _guard = shared.lock_clan(&clan).await;
+
+                // Both Updates only make progress if they hold their Clan Locks at the same time
+                barrier.wait().await;
+
+                shared.with_storage(|storage| {
+                    storage
+                        .get_mut(&clan, &season)
+                        .unwrap()
+                        .ingest_games(PlayerTag("#2PP".to_string()), 100)
+                        .unwrap();
+                });
+            }
+        };
+
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            tokio::join!(update(first.clone()), update(second.clone()))
+        })
+        .await
+        .expect("The Updates for different Clans blocked each other");
+
+        let storage = shared.snapshot();
+        assert_eq!(1, storage.get(&first, &season).unwrap().games.len());
+        assert_eq!(1, storage.get(&second, &season).unwrap().games.len());
+    }
+
+    #[tokio::test]
+    async fn same_clan_serializes() {
+        let clan = ClanTag("#2L99VLJ9P".to_string());
+        let shared = SharedStorage::new(Storage::new_with_clans([clan.clone()]));
+
+        let guard = shared.lock_clan(&clan).await;
+        assert!(shared.try_lock_clan(&clan).is_none());
+        assert!(shared.try_lock_clan(&ClanTag("#9QQ".to_string())).is_some());
+
+        drop(guard);
+        assert!(shared.try_lock_clan(&clan).is_some());
+    }
+}
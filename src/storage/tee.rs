@@ -0,0 +1,109 @@
+use std::{future::Future, pin::Pin};
+
+use crate::{StorageBackend, StorageError};
+
+/// Writes to all the Backends at once and loads from the first one that has the Content.
+///
+/// A Write only succeeds if it succeeded for every Backend, otherwise the first Error is
+/// returned once all the Writes have finished, so some Backends may still hold the new Content.
+pub struct TeeStorage {
+    backends: Vec<Box<dyn StorageBackend>>,
+}
+
+impl TeeStorage {
+    pub fn new(backends: Vec<Box<dyn StorageBackend>>) -> Self {
+        Self { backends }
+    }
+}
+
+impl StorageBackend for TeeStorage {
+    #[tracing::instrument(skip(self, content))]
+    fn write(
+        &mut self,
+        content: Vec<u8>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), StorageError>> + Send + 'static>> {
+        let writes: Vec<_> = self
+            .backends
+            .iter_mut()
+            .map(|backend| backend.write(content.clone()))
+            .collect();
+
+        Box::pin(async move {
+            tracing::trace!("Storing to {} Backends", writes.len());
+
+            let handles: Vec<_> = writes.into_iter().map(tokio::spawn).collect();
+
+            let mut result = Ok(());
+            for handle in handles {
+                let res = handle
+                    .await
+                    .unwrap_or_else(|e| Err(StorageError::Backend(e.to_string())));
+
+                if let Err(e) = res {
+                    tracing::error!("Storing {:?}", e);
+                    if result.is_ok() {
+                        result = Err(e);
+                    }
+                }
+            }
+            result
+        })
+    }
+
+    fn load(
+        &mut self,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<u8>, StorageError>> + Send + 'static>> {
+        let loads: Vec<_> = self
+            .backends
+            .iter_mut()
+            .map(|backend| backend.load())
+            .collect();
+
+        Box::pin(async move {
+            let mut result = Err(StorageError::NotFound);
+            for load in loads {
+                result = load.await;
+                if result.is_ok() {
+                    break;
+                }
+            }
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClanTag, MemoryStorage, Storage};
+
+    #[tokio::test]
+    async fn write_to_all() {
+        let first = MemoryStorage::new();
+        let second = MemoryStorage::new();
+        let mut backend = TeeStorage::new(vec![Box::new(first.clone()), Box::new(second.clone())]);
+
+        let mut storage = Storage::new_with_clans([ClanTag("#2PP".to_string())]);
+        storage.save(&mut backend).await.unwrap();
+
+        assert!(first.content().is_some());
+        assert_eq!(first.content(), second.content());
+
+        let loaded = Storage::load(&mut backend).await.unwrap();
+        assert_eq!(storage.checksum(), loaded.checksum());
+    }
+
+    #[tokio::test]
+    async fn load_first_available() {
+        let storage = Storage::new_with_clans([ClanTag("#2PP".to_string())]);
+        let mut backend = TeeStorage::new(vec![
+            Box::new(MemoryStorage::new()),
+            Box::new(MemoryStorage::with_content(
+                serde_json::to_vec(&storage).unwrap(),
+            )),
+        ]);
+
+        let loaded = Storage::load(&mut backend).await.unwrap();
+        assert_eq!(storage.checksum(), loaded.checksum());
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{ClanStorage, ScoreConfig};
+
+const HEADERS: [&str; 6] = ["Name", "War Stars", "CWL Stars", "Loot", "Games", "Total"];
+
+impl ClanStorage {
+    /// Formats the Summaries of all Players as a fixed-width Table for the Terminal, ordered by
+    /// their total Score and then by Name
+    pub fn summary_table(&self) -> String {
+        let cfg = self.score_config(&ScoreConfig::default());
+
+        let mut rows: Vec<(f64, [String; 6])> = self
+            .weighted_summaries(&cfg)
+            .map(|(tag, summary)| {
+                let name = self.player_names.get(&tag).cloned().unwrap_or(tag.0);
+                let total = summary.total_score_with(&cfg);
+                (
+                    total,
+                    [
+                        name,
+                        summary.war_stars.to_string(),
+                        summary.cwl_stars.to_string(),
+                        summary.raid_loot.to_string(),
+                        summary.games_score.to_string(),
+                        format!("{:.2}", cfg.rounding.apply(total)),
+                    ],
+                )
+            })
+            .collect();
+        rows.sort_by(|(t1, r1), (t2, r2)| t2.total_cmp(t1).then_with(|| r1[0].cmp(&r2[0])));
+
+        let mut widths = HEADERS.map(|header| header.chars().count());
+        for (_, row) in rows.iter() {
+            for (width, cell) in widths.iter_mut().zip(row.iter()) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let headers = HEADERS.map(|header| header.to_string());
+        std::iter::once(&headers)
+            .chain(rows.iter().map(|(_, row)| row))
+            .map(|row| {
+                row.iter()
+                    .zip(widths.iter())
+                    .enumerate()
+                    .map(|(column, (cell, width))| {
+                        // The Name is left aligned, all the Numbers are right aligned
+                        let padding = " ".repeat(width - cell.chars().count());
+                        if column == 0 {
+                            format!("{}{}", cell, padding)
+                        } else {
+                            format!("{}{}", padding, cell)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("  ")
+            })
+            .map(|line| line.trim_end().to_string() + "\n")
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ClanStorage, PlayerGamesStats, PlayerTag};
+
+    #[test]
+    fn summary_table() {
+        let mut clan = ClanStorage::default();
+        for (tag, name, games) in [
+            ("#2PP", "A", 1000),
+            ("#2QQ", "A much longer Name", 5000),
+            ("#2RR", "Medium", 0),
+        ] {
+            let tag = PlayerTag(tag.to_string());
+            clan.player_names.insert(tag.clone(), name.to_string());
+            clan.games.insert(
+                tag,
+                PlayerGamesStats {
+                    start_score: Some(0),
+                    end_score: games,
+                },
+            );
+        }
+
+        let expected = [
+            "Name                War Stars  CWL Stars  Loot  Games   Total",
+            "A much longer Name          0          0     0   5000  100.00",
+            "A                           0          0     0   1000   20.00",
+            "Medium                      0          0     0      0    0.00",
+        ];
+        assert_eq!(
+            expected.map(|line| format!("{}\n", line)).concat(),
+            clan.summary_table()
+        );
+    }
+}
@@ -1,5 +1,67 @@
 use serde::{Deserialize, Serialize};
 
+/// All the Characters that can appear in a Tag after the `#`
+pub const TAG_ALPHABET: &str = "0289PYLQGRJCUV";
+
+/// The Reasons why a Tag could not be parsed
+#[derive(Debug, PartialEq, Eq)]
+pub enum TagError {
+    /// There is nothing after the `#`
+    Empty,
+    /// The Tag contains a Character that is not part of the [`TAG_ALPHABET`]
+    InvalidCharacter(char),
+}
+
+impl std::fmt::Display for TagError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "Expected the Tag to contain more than just #"),
+            Self::InvalidCharacter(c) => {
+                write!(
+                    f,
+                    "{:?} is not one of the Tag Characters {}",
+                    c, TAG_ALPHABET
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for TagError {}
+
+/// Uppercases the Tag and replaces the Letter `O` with the Digit `0`, as these are commonly
+/// mixed up when Tags are typed by hand
+pub(crate) fn normalize_tag(raw: &str) -> String {
+    raw.trim()
+        .chars()
+        .map(|c| match c.to_ascii_uppercase() {
+            'O' => '0',
+            c => c,
+        })
+        .collect()
+}
+
+/// Percent-encodes the `#` of the Tag, so it can be used in the Path of an API Request
+fn url_encode(tag: &str) -> String {
+    tag.replace('#', "%23")
+}
+
+/// Validates and normalizes the raw Tag
+pub(crate) fn parse_tag(raw: &str) -> Result<String, TagError> {
+    let tag = normalize_tag(raw);
+
+    // The # is commonly left out when the Tag is typed by hand
+    let content = tag.strip_prefix('#').unwrap_or(&tag);
+    if content.is_empty() {
+        return Err(TagError::Empty);
+    }
+    if let Some(c) = content.chars().find(|c| !TAG_ALPHABET.contains(*c)) {
+        return Err(TagError::InvalidCharacter(c));
+    }
+
+    Ok(format!("#{}", content))
+}
+
 mod custom_serde {
     use serde::de::Visitor;
 
@@ -9,40 +71,24 @@ mod custom_serde {
         type Value = String;
 
         fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-            formatter.write_str("Expected a String starting with #...")
-        }
-
-        fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
-        where
-            E: serde::de::Error,
-        {
-            if !v.starts_with("#") {
-                return Err(serde::de::Error::invalid_value(
-                    serde::de::Unexpected::Str(v.as_str()),
-                    &"#",
-                ));
-            }
-
-            Ok(v)
+            formatter.write_str("Expected a Tag like #2PP")
         }
 
         fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
         where
             E: serde::de::Error,
         {
-            if !v.starts_with("#") {
-                return Err(serde::de::Error::invalid_value(
+            super::parse_tag(v).map_err(|e| {
+                serde::de::Error::invalid_value(
                     serde::de::Unexpected::Str(v),
-                    &"#",
-                ));
-            }
-
-            Ok(v.to_owned())
+                    &e.to_string().as_str(),
+                )
+            })
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 pub struct ClanTag(pub String);
 
 impl<'de> Deserialize<'de> for ClanTag {
@@ -64,7 +110,28 @@ impl Serialize for ClanTag {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+impl ClanTag {
+    /// The Tag with the `#` encoded as `%23`, like the API expects it in URLs
+    pub fn url_encoded(&self) -> String {
+        url_encode(&self.0)
+    }
+}
+
+impl std::fmt::Display for ClanTag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::str::FromStr for ClanTag {
+    type Err = TagError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_tag(s).map(Self)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 pub struct WarTag(pub String);
 
 impl<'de> Deserialize<'de> for WarTag {
@@ -86,7 +153,28 @@ impl Serialize for WarTag {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+impl WarTag {
+    /// The Tag with the `#` encoded as `%23`, like the API expects it in URLs
+    pub fn url_encoded(&self) -> String {
+        url_encode(&self.0)
+    }
+}
+
+impl std::fmt::Display for WarTag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::str::FromStr for WarTag {
+    type Err = TagError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_tag(s).map(Self)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 pub struct PlayerTag(pub String);
 
 impl<'de> Deserialize<'de> for PlayerTag {
@@ -108,22 +196,127 @@ impl Serialize for PlayerTag {
     }
 }
 
+impl PlayerTag {
+    /// The Tag with the `#` encoded as `%23`, like the API expects it in URLs
+    pub fn url_encoded(&self) -> String {
+        url_encode(&self.0)
+    }
+}
+
+impl std::fmt::Display for PlayerTag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::str::FromStr for PlayerTag {
+    type Err = TagError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_tag(s).map(Self)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[derive(Debug, Deserialize)]
+    struct Test<T> {
+        inner: T,
+    }
+
     #[test]
     fn deserialize_clantag() {
-        #[derive(Debug, Deserialize)]
-        struct Test {
-            inner: ClanTag,
-        }
-
         assert_eq!(
-            ClanTag("#Testing".to_string()),
-            serde_json::from_str::<Test>("{ \"inner\": \"#Testing\" }")
+            ClanTag("#2L99VLJ9P".to_string()),
+            serde_json::from_str::<Test<ClanTag>>("{ \"inner\": \"#2L99VLJ9P\" }")
                 .unwrap()
                 .inner,
         );
     }
+
+    #[test]
+    fn reject_invalid() {
+        for raw in ["#2P!", "#2PI", "#2pi", "#", ""] {
+            let content = format!("{{ \"inner\": \"{}\" }}", raw);
+
+            assert!(serde_json::from_str::<Test<ClanTag>>(&content).is_err());
+            assert!(serde_json::from_str::<Test<WarTag>>(&content).is_err());
+            assert!(serde_json::from_str::<Test<PlayerTag>>(&content).is_err());
+        }
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(Ok("#2PP".to_string()), parse_tag(" #2PP "));
+        assert_eq!(Ok("#2PP0".to_string()), parse_tag("2PP0"));
+        assert_eq!(parse_tag("#2PP0"), parse_tag("2PP0"));
+        assert_eq!(Err(TagError::Empty), parse_tag(""));
+        assert_eq!(Err(TagError::Empty), parse_tag("  "));
+        assert_eq!(Err(TagError::Empty), parse_tag("#"));
+        assert_eq!(Err(TagError::InvalidCharacter('I')), parse_tag("#2pi"));
+        assert_eq!(Err(TagError::InvalidCharacter('!')), parse_tag("#2P!"));
+    }
+
+    #[test]
+    fn display_from_str() {
+        let clan: ClanTag = "#2l99vlj9p".parse().unwrap();
+        assert_eq!("#2L99VLJ9P", clan.to_string());
+        assert_eq!(Ok(clan.clone()), clan.to_string().parse());
+
+        let war: WarTag = "#8YY".parse().unwrap();
+        assert_eq!(Ok(war.clone()), war.to_string().parse());
+        assert_eq!(Ok(war), "8YY".parse());
+
+        let player: PlayerTag = "#2PO".parse().unwrap();
+        assert_eq!("#2P0", format!("{}", player));
+        assert_eq!(Ok(player.clone()), player.to_string().parse());
+
+        assert_eq!(
+            Err(TagError::InvalidCharacter('!')),
+            "#2P!".parse::<PlayerTag>()
+        );
+    }
+
+    #[test]
+    fn url_encoded() {
+        assert_eq!("%232PP0", ClanTag("#2PP0".to_string()).url_encoded());
+        assert_eq!("%232PP0", WarTag("#2PP0".to_string()).url_encoded());
+        assert_eq!("%232PP0", PlayerTag("#2PP0".to_string()).url_encoded());
+    }
+
+    #[test]
+    fn ordering() {
+        let mut tags: Vec<_> = ["#9LL", "#2QQ", "#2PP", "#8YY"]
+            .into_iter()
+            .map(|tag| PlayerTag(tag.to_string()))
+            .collect();
+        tags.sort();
+
+        assert_eq!(
+            vec!["#2PP", "#2QQ", "#8YY", "#9LL"],
+            tags.iter().map(|tag| tag.0.as_str()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn normalize() {
+        assert_eq!("#ABC0", normalize_tag("#abc0"));
+        assert_eq!(normalize_tag("#abc0"), normalize_tag("#ABCO"));
+
+        let parse = |raw: &str| {
+            serde_json::from_str::<Test<PlayerTag>>(&format!("{{ \"inner\": \"{}\" }}", raw))
+                .map(|test| test.inner)
+                .ok()
+        };
+
+        // A, B and C are not part of the Tag Alphabet, so these are still rejected afterwards
+        assert_eq!(None, parse("#abc0"));
+        assert_eq!(None, parse("#ABCO"));
+
+        assert_eq!(Some(PlayerTag("#2PP".to_string())), parse("#2pp"));
+        assert_eq!(Some(PlayerTag("#2P0".to_string())), parse("#2PO"));
+        assert_eq!(parse("#2p0"), parse("#2po"));
+    }
 }
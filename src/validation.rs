@@ -0,0 +1,96 @@
+use crate::{ClanStorage, ClanTag, PlayerTag, Season, Storage, CWL_MAX_STARS_PER_ROUND};
+
+/// A Problem found in the stored Data, which likely stems from a bug in the collection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationIssue {
+    /// The Player has more Stars recorded for a CWL Round than are possible
+    CwlStarsExceeded {
+        round: usize,
+        player: PlayerTag,
+        stars: usize,
+    },
+}
+
+impl ClanStorage {
+    /// Checks the Stats for impossible Values
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+
+        for (round, war) in self.cwl.wars.iter().enumerate() {
+            for (player, stats) in war.members.iter() {
+                let stars: usize = stats.attacks.iter().map(|a| a.stars).sum();
+                if stars > CWL_MAX_STARS_PER_ROUND {
+                    issues.push(ValidationIssue::CwlStarsExceeded {
+                        round,
+                        player: player.clone(),
+                        stars,
+                    });
+                }
+            }
+        }
+
+        issues
+    }
+}
+
+impl Storage {
+    /// Validates the Stats of every Clan and Season
+    pub fn validate_all(&self) -> Vec<(ClanTag, Season, ValidationIssue)> {
+        self.iter()
+            .flat_map(|(clan, season, stats)| {
+                stats
+                    .validate()
+                    .into_iter()
+                    .map(move |issue| (clan.clone(), season.clone(), issue))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{CwlWarStats, MemberWarStats, WarAttack};
+
+    #[test]
+    fn clamp_cwl_stars() {
+        let player = PlayerTag("#2PP".to_string());
+        let attack = |stars| WarAttack {
+            destruction: 100,
+            stars,
+            duration: 100,
+            target: None,
+            order: 0,
+            attacker_townhall: None,
+            defender_townhall: None,
+        };
+
+        let mut clan = ClanStorage::default();
+        clan.cwl.wars.push(CwlWarStats {
+            members: [(
+                player.clone(),
+                MemberWarStats {
+                    attacks: vec![attack(3), attack(1)],
+                },
+            )]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        });
+        clan.player_names
+            .insert(player.clone(), "Player".to_string());
+
+        assert_eq!(
+            vec![ValidationIssue::CwlStarsExceeded {
+                round: 0,
+                player: player.clone(),
+                stars: 4,
+            }],
+            clan.validate()
+        );
+
+        let (_, summary) = clan.players_summary().next().unwrap();
+        assert_eq!(3, summary.cwl_stars);
+    }
+}